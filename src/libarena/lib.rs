@@ -305,6 +305,20 @@ impl DroplessArena {
         self.chunks.borrow().iter().any(|chunk| chunk.start() <= ptr && ptr < chunk.end())
     }
 
+    /// The number of bytes handed out by this arena so far, counting the
+    /// unused tail of every full chunk. This walks all chunks, so it is only
+    /// meant for debugging statistics.
+    pub fn allocated_bytes(&self) -> usize {
+        let chunks = self.chunks.borrow();
+        match chunks.split_last() {
+            Some((last_chunk, full_chunks)) => {
+                let full: usize = full_chunks.iter().map(|chunk| chunk.storage.cap()).sum();
+                full + (self.ptr.get() as usize - last_chunk.start() as usize)
+            }
+            None => 0,
+        }
+    }
+
     #[inline]
     fn align(&self, align: usize) {
         let final_address = ((self.ptr.get() as usize) + align - 1) & !(align - 1);
@@ -448,6 +462,10 @@ impl SyncDroplessArena {
         self.lock.lock().in_arena(ptr)
     }
 
+    pub fn allocated_bytes(&self) -> usize {
+        self.lock.lock().allocated_bytes()
+    }
+
     #[inline(always)]
     pub fn alloc_raw(&self, bytes: usize, align: usize) -> &mut [u8] {
         // Extend the lifetime of the result since it's limited to the lock guard
@@ -68,7 +68,8 @@ pub enum MirPhase {
     Build = 0,
     Const = 1,
     Validated = 2,
-    Optimized = 3,
+    DropsElaborated = 3,
+    Optimized = 4,
 }
 
 impl MirPhase {
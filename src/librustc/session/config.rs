@@ -426,6 +426,7 @@ pub enum PrintRequest {
     TlsModels,
     TargetSpec,
     NativeStaticLibs,
+    MirPassOrder,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
@@ -1264,7 +1265,7 @@ options! {DebuggingOptions, DebuggingSetter, basic_debugging_options,
     dump_mir: Option<String> = (None, parse_opt_string, [UNTRACKED],
         "dump MIR state to file.
         `val` is used to select which passes and functions to dump. For example:
-        `all` matches all passes and functions,
+        `all` (or `*`) matches all passes and functions,
         `foo` matches all passes for functions whose name contains 'foo',
         `foo & ConstProp` only the 'ConstProp' pass for function names containing 'foo',
         `foo | bar` all passes for function names containing 'foo' or 'bar'."),
@@ -1275,12 +1276,33 @@ options! {DebuggingOptions, DebuggingSetter, basic_debugging_options,
         "in addition to `.mir` files, create graphviz `.dot` files"),
     dump_mir_exclude_pass_number: bool = (false, parse_bool, [UNTRACKED],
         "if set, exclude the pass number when dumping MIR (used in tests)"),
+    dump_mir_skipped: bool = (false, parse_bool, [UNTRACKED],
+        "if set, dump the MIR of passes that are skipped, as `skipped`, when dumping MIR"),
+    dump_mir_diff: Option<String> = (None, parse_opt_string, [UNTRACKED],
+        "write a diff of the MIR before and after each pass whose name contains `val`"),
+    erase_regions_only: Option<String> = (None, parse_opt_string, [TRACKED],
+        "only erase the regions of the functions whose path contains `val`, for bisecting"),
     mir_emit_retag: bool = (false, parse_bool, [TRACKED],
         "emit Retagging MIR statements, interpreted e.g., by miri; implies -Zmir-opt-level=0"),
     perf_stats: bool = (false, parse_bool, [UNTRACKED],
         "print some performance-related statistics"),
     hir_stats: bool = (false, parse_bool, [UNTRACKED],
         "print some statistics about AST and HIR"),
+    mir_stats: bool = (false, parse_bool, [UNTRACKED],
+        "print some statistics about the work done by MIR passes"),
+    mir_mem_stats: bool = (false, parse_bool, [UNTRACKED],
+        "print how many bytes the interners allocated while EraseRegions ran"),
+    verify_erased: bool = (false, parse_bool, [UNTRACKED],
+        "report a bug for every region left in the MIR after EraseRegions"),
+    time_mir_passes: bool = (false, parse_bool, [UNTRACKED],
+        "measure the total time spent in each MIR pass, printed at the end"),
+    warn_nop_density: bool = (false, parse_bool, [UNTRACKED],
+        "warn about functions whose optimized MIR is mostly `Nop` statements"),
+    cross_check_erase: bool = (false, parse_bool, [UNTRACKED],
+        "check that each type EraseRegions erases has no regions left and erases to itself"),
+    test_unerase: bool = (false, parse_bool, [UNTRACKED],
+        "self-check that erasing a copy of the MIR with its erased regions renamed gives \
+         back the same MIR; the MIR itself is not changed"),
     always_encode_mir: bool = (false, parse_bool, [TRACKED],
         "encode MIR of all functions into the crate metadata"),
     osx_rpath_install_name: bool = (false, parse_bool, [TRACKED],
@@ -1645,7 +1667,8 @@ pub fn rustc_short_optgroups() -> Vec<RustcOptGroup> {
              print on stdout",
             "[crate-name|file-names|sysroot|cfg|target-list|\
              target-cpus|target-features|relocation-models|\
-             code-models|tls-models|target-spec-json|native-static-libs]",
+             code-models|tls-models|target-spec-json|native-static-libs|\
+             mir-pass-order]",
         ),
         opt::flagmulti_s("g", "", "Equivalent to -C debuginfo=2"),
         opt::flagmulti_s("O", "", "Equivalent to -C opt-level=2"),
@@ -1977,6 +2000,13 @@ pub fn build_session_options_and_crate_config(
         );
     }
 
+    if debugging_opts.query_threads.unwrap_or(1) > 1 && debugging_opts.mir_mem_stats {
+        early_error(
+            error_format,
+            "-Z mir-mem-stats is incompatible with multiple query threads",
+        );
+    }
+
     if codegen_units == Some(0) {
         early_error(
             error_format,
@@ -2177,6 +2207,17 @@ pub fn build_session_options_and_crate_config(
                 );
             }
         }
+        "mir-pass-order" => {
+            if is_unstable_enabled {
+                PrintRequest::MirPassOrder
+            } else {
+                early_error(
+                    error_format,
+                    "the `-Z unstable-options` flag must also be passed to \
+                     enable the mir-pass-order print option",
+                );
+            }
+        }
         req => early_error(error_format, &format!("unknown print request `{}`", req)),
     }));
 
@@ -3126,6 +3167,24 @@ mod tests {
         assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
         opts.debugging_opts.dump_mir_graphviz = true;
         assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
+        opts.debugging_opts.dump_mir_skipped = true;
+        assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
+        opts.debugging_opts.dump_mir_diff = Some(String::from("abc"));
+        assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
+        opts.debugging_opts.mir_stats = true;
+        assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
+        opts.debugging_opts.verify_erased = true;
+        assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
+        opts.debugging_opts.time_mir_passes = true;
+        assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
+        opts.debugging_opts.warn_nop_density = true;
+        assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
+        opts.debugging_opts.test_unerase = true;
+        assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
+        opts.debugging_opts.cross_check_erase = true;
+        assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
+        opts.debugging_opts.mir_mem_stats = true;
+        assert_eq!(reference.dep_tracking_hash(), opts.dep_tracking_hash());
 
         // Make sure changing a [TRACKED] option changes the hash
         opts = reference.clone();
@@ -3183,6 +3242,10 @@ mod tests {
         opts = reference.clone();
         opts.debugging_opts.cross_lang_lto = CrossLangLto::LinkerPluginAuto;
         assert!(reference.dep_tracking_hash() != opts.dep_tracking_hash());
+
+        opts = reference.clone();
+        opts.debugging_opts.erase_regions_only = Some(String::from("abc"));
+        assert!(reference.dep_tracking_hash() != opts.dep_tracking_hash());
     }
 
     #[test]
@@ -12,7 +12,7 @@ use middle::dependency_format;
 use session::config::{OutputType, Lto};
 use session::search_paths::{PathKind, SearchPath};
 use util::nodemap::{FxHashMap, FxHashSet};
-use util::common::{duration_to_secs_str, ErrorReported};
+use util::common::{duration_to_secs_str, print_time_passes_entry, ErrorReported};
 use util::common::ProfileQueriesMsg;
 
 use rustc_data_structures::base_n;
@@ -173,6 +173,18 @@ pub struct PerfStats {
     pub normalize_ty_after_erasing_regions: AtomicUsize,
     /// Number of times this query is invoked.
     pub normalize_projection_ty: AtomicUsize,
+    /// The accumulated time spent in each MIR pass, by pass name. Only
+    /// recorded with `-Z time-mir-passes`.
+    pub mir_pass_times: Lock<FxHashMap<String, Duration>>,
+    /// The total number of regions, types, substs and constants changed by
+    /// `EraseRegions`, over all MIR bodies.
+    pub erased_regions: AtomicUsize,
+    pub erased_tys: AtomicUsize,
+    pub erased_substs: AtomicUsize,
+    pub erased_consts: AtomicUsize,
+    /// The bytes the interners' arena grew by while `EraseRegions` ran.
+    /// Only recorded with `-Z mir-mem-stats`.
+    pub erase_regions_interned_bytes: AtomicUsize,
 }
 
 /// Enum to support dispatch of one-time diagnostics (in Session.diag_once)
@@ -855,6 +867,27 @@ impl Session {
                  self.perf_stats.normalize_ty_after_erasing_regions.load(Ordering::Relaxed));
         println!("normalize_projection_ty:                       {}",
                  self.perf_stats.normalize_projection_ty.load(Ordering::Relaxed));
+        println!("EraseRegions regions/types/substs/consts:      {}/{}/{}/{}",
+                 self.perf_stats.erased_regions.load(Ordering::Relaxed),
+                 self.perf_stats.erased_tys.load(Ordering::Relaxed),
+                 self.perf_stats.erased_substs.load(Ordering::Relaxed),
+                 self.perf_stats.erased_consts.load(Ordering::Relaxed));
+    }
+
+    pub fn print_mir_pass_times(&self) {
+        let mut times: Vec<_> = self.perf_stats.mir_pass_times.lock()
+            .iter()
+            .map(|(name, &time)| (name.clone(), time))
+            .collect();
+        times.sort();
+        for (name, time) in times {
+            print_time_passes_entry(true, &format!("MIR pass {}", name), time);
+        }
+    }
+
+    pub fn print_mir_mem_stats(&self) {
+        println!("EraseRegions interned:                        {} bytes",
+                 self.perf_stats.erase_regions_interned_bytes.load(Ordering::Relaxed));
     }
 
     /// We want to know if we're allowed to do an optimization for crate foo from -z fuel=foo=n.
@@ -1189,6 +1222,12 @@ pub fn build_session_(
             queries_canonicalized: AtomicUsize::new(0),
             normalize_ty_after_erasing_regions: AtomicUsize::new(0),
             normalize_projection_ty: AtomicUsize::new(0),
+            mir_pass_times: Lock::new(FxHashMap::default()),
+            erased_regions: AtomicUsize::new(0),
+            erased_tys: AtomicUsize::new(0),
+            erased_substs: AtomicUsize::new(0),
+            erased_consts: AtomicUsize::new(0),
+            erase_regions_interned_bytes: AtomicUsize::new(0),
         },
         code_stats: Default::default(),
         optimization_fuel_crate,
@@ -2222,6 +2222,14 @@ macro_rules! sty_debug_print {
 }
 
 impl<'a, 'tcx> TyCtxt<'a, 'tcx, 'tcx> {
+    /// The bytes allocated so far by the arena that this context interns
+    /// into, for `-Z mir-mem-stats`. Nothing is counted while interning; this
+    /// reads the arena's chunks, so it is only meant to be called around a
+    /// pass when the statistics were asked for.
+    pub fn interned_bytes(self) -> usize {
+        self.interners.arena.allocated_bytes()
+    }
+
     pub fn print_debug_stats(self) {
         sty_debug_print!(
             self,
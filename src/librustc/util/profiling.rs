@@ -1,5 +1,6 @@
 use session::config::Options;
 
+use std::collections::BTreeMap;
 use std::fs;
 use std::io::{self, StderrLock, Write};
 use std::time::{Duration, Instant};
@@ -127,10 +128,20 @@ define_categories! {
     Other,
 }
 
+/// The counts recorded with `SelfProfiler::record_counts` for one event.
+#[derive(Default)]
+struct EventCounts {
+    /// How many times the event was recorded.
+    occurrences: u64,
+    /// The sum of each count, in the order the counts were first recorded.
+    totals: Vec<(&'static str, u64)>,
+}
+
 pub struct SelfProfiler {
     timer_stack: Vec<ProfileCategory>,
     data: CategoryData,
     current_timer: Instant,
+    event_counts: BTreeMap<&'static str, EventCounts>,
 }
 
 impl SelfProfiler {
@@ -139,6 +150,7 @@ impl SelfProfiler {
             timer_stack: Vec::new(),
             data: CategoryData::new(),
             current_timer: Instant::now(),
+            event_counts: BTreeMap::new(),
         };
 
         profiler.start_activity(ProfileCategory::Other);
@@ -178,6 +190,20 @@ impl SelfProfiler {
         self.data.query_counts.set(category, (hits + 1, total));
     }
 
+    /// Adds `counts` to the totals of `event`, e.g. how much work a MIR pass
+    /// did on one function. The totals over the crate are printed along with
+    /// the timings.
+    pub fn record_counts(&mut self, event: &'static str, counts: &[(&'static str, u64)]) {
+        let event_counts = self.event_counts.entry(event).or_default();
+        event_counts.occurrences += 1;
+        for &(name, count) in counts {
+            match event_counts.totals.iter_mut().find(|total| total.0 == name) {
+                Some(total) => total.1 += count,
+                None => event_counts.totals.push((name, count)),
+            }
+        }
+    }
+
     pub fn end_activity(&mut self, category: ProfileCategory) {
         match self.timer_stack.pop() {
             None => bug!("end_activity() was called but there was no running activity"),
@@ -244,6 +270,17 @@ impl SelfProfiler {
 
         self.data.print(&mut lock);
 
+        if !self.event_counts.is_empty() {
+            writeln!(lock).unwrap();
+            for (event, counts) in &self.event_counts {
+                let totals: Vec<_> = counts.totals.iter()
+                    .map(|&(name, total)| format!("{}: {}", name, total))
+                    .collect();
+                writeln!(lock, "{} ({} times): {}", event, counts.occurrences, totals.join(", "))
+                    .unwrap();
+            }
+        }
+
         writeln!(lock).unwrap();
         writeln!(lock, "Optimization level: {:?}", opts.optimize).unwrap();
 
@@ -258,9 +295,19 @@ impl SelfProfiler {
                     opts.optimize,
                     if opts.incremental.is_some() { "true" } else { "false" });
 
-        let json = format!("{{ \"category_data\": {}, \"compilation_options\": {} }}",
+        let event_counts: Vec<_> = self.event_counts.iter().map(|(event, counts)| {
+            let totals: Vec<_> = counts.totals.iter()
+                .map(|&(name, total)| format!("\"{}\": {}", name, total))
+                .collect();
+            format!("{{ \"event\": \"{}\", \"occurrences\": {}, \"totals\": {{ {} }} }}",
+                    event, counts.occurrences, totals.join(", "))
+        }).collect();
+
+        let json = format!("{{ \"category_data\": {}, \"compilation_options\": {}, \
+                            \"event_counts\": [{}] }}",
                         category_data,
-                        compilation_options);
+                        compilation_options,
+                        event_counts.join(","));
 
         fs::write("self_profiler_results.json", json).unwrap();
     }
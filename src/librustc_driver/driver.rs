@@ -343,6 +343,14 @@ pub fn compile_input(
         sess.print_perf_stats();
     }
 
+    if sess.opts.debugging_opts.time_mir_passes {
+        sess.print_mir_pass_times();
+    }
+
+    if sess.opts.debugging_opts.mir_mem_stats {
+        sess.print_mir_mem_stats();
+    }
+
     if sess.opts.debugging_opts.self_profile {
         sess.print_profiler_results();
     }
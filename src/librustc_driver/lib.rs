@@ -996,9 +996,11 @@ impl RustcDefaultCalls {
                         ofile: &Option<PathBuf>)
                         -> Compilation {
         use rustc::session::config::PrintRequest::*;
-        // PrintRequest::NativeStaticLibs is special - printed during linking
+        // PrintRequest::NativeStaticLibs is special - printed during linking,
+        // and so is PrintRequest::MirPassOrder - printed while optimizing MIR
         // (empty iterator returns true)
-        if sess.opts.prints.iter().all(|&p| p == PrintRequest::NativeStaticLibs) {
+        if sess.opts.prints.iter().all(|&p| p == PrintRequest::NativeStaticLibs ||
+                                            p == PrintRequest::MirPassOrder) {
             return Compilation::Continue;
         }
 
@@ -1089,6 +1091,7 @@ impl RustcDefaultCalls {
                 }
                 // Any output here interferes with Cargo's parsing of other printed output
                 PrintRequest::NativeStaticLibs => {}
+                PrintRequest::MirPassOrder => {}
             }
         }
         return Compilation::Stop;
@@ -0,0 +1,613 @@
+//! Tests for `EraseRegions` and the other MIR passes that can run on MIR built
+//! by hand, without a crate to compile.
+
+use super::{errors, test_env, test_env_with_options, test_options, EMPTY_SOURCE_STR};
+use rustc::hir;
+use rustc::infer::canonical::Canonical;
+use rustc::mir::visit::{MutVisitor, PlaceContext};
+use rustc::mir::*;
+use rustc::ty::{self, Ty, TyCtxt};
+use rustc_data_structures::fx::FxHashSet;
+use rustc_data_structures::indexed_vec::{Idx, IndexVec};
+use rustc_data_structures::sync::{self, ParallelIterator};
+use rustc_mir::transform::{
+    check_regions_erased, erase_regions, remove_nops, unerase_regions, MirPass, MirSource,
+};
+use rustc_target::spec::abi::Abi;
+use syntax::ast;
+use syntax::symbol::Symbol;
+use syntax_pos::DUMMY_SP;
+
+use std::iter;
+use std::ptr;
+use std::sync::atomic::Ordering;
+
+/// Builds the MIR of a function returning `()`, with a temporary of each of
+/// `local_tys`, numbered from `_1`, and one returning basic block for each of
+/// `blocks`. It is in `MirPhase::DropsElaborated`, so it is ready for `EraseRegions`.
+fn mk_mir<'a, 'tcx>(
+    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+    local_tys: &[Ty<'tcx>],
+    blocks: Vec<Vec<StatementKind<'tcx>>>,
+) -> Mir<'tcx> {
+    let source_info = SourceInfo {
+        span: DUMMY_SP,
+        scope: OUTERMOST_SOURCE_SCOPE,
+    };
+    let mut local_decls = IndexVec::new();
+    local_decls.push(LocalDecl::new_return_place(tcx.mk_unit(), DUMMY_SP));
+    for &ty in local_tys {
+        local_decls.push(LocalDecl::new_temp(ty, DUMMY_SP));
+    }
+    let basic_blocks = blocks
+        .into_iter()
+        .map(|statements| BasicBlockData {
+            statements: statements
+                .into_iter()
+                .map(|kind| Statement { source_info, kind })
+                .collect(),
+            terminator: Some(Terminator {
+                source_info,
+                kind: TerminatorKind::Return,
+            }),
+            is_cleanup: false,
+        })
+        .collect();
+    let mut source_scopes = IndexVec::new();
+    source_scopes.push(SourceScopeData {
+        span: DUMMY_SP,
+        parent_scope: None,
+    });
+    let mut mir = Mir::new(
+        basic_blocks,
+        source_scopes,
+        ClearCrossCrate::Clear,
+        IndexVec::new(),
+        None,
+        local_decls,
+        0,
+        vec![],
+        DUMMY_SP,
+        vec![],
+    );
+    mir.phase = MirPhase::DropsElaborated;
+    mir
+}
+
+/// `_dest = &'region _src`
+fn mk_borrow<'tcx>(dest: usize, region: ty::Region<'tcx>, src: usize) -> StatementKind<'tcx> {
+    StatementKind::Assign(
+        Place::Local(Local::new(dest)),
+        box Rvalue::Ref(region, BorrowKind::Shared, Place::Local(Local::new(src))),
+    )
+}
+
+/// The region of the borrow `mk_borrow` built as statement `index` of `block`.
+fn borrow_region<'tcx>(mir: &Mir<'tcx>, block: usize, index: usize) -> ty::Region<'tcx> {
+    let statement = &mir.basic_blocks()[BasicBlock::new(block)].statements[index];
+    if let StatementKind::Assign(_, ref rvalue) = statement.kind {
+        if let Rvalue::Ref(region, ..) = **rvalue {
+            return region;
+        }
+    }
+    panic!("expected a borrow, found {:?}", statement);
+}
+
+/// `fn(&'region u32)`, where `region` is bound by the `fn` itself if it is
+/// late-bound at the innermost binder.
+fn mk_fn_taking_ref<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, region: ty::Region<'tcx>) -> Ty<'tcx> {
+    tcx.mk_fn_ptr(ty::Binder::bind(tcx.mk_fn_sig(
+        iter::once(tcx.mk_imm_ref(region, tcx.types.u32)),
+        tcx.mk_unit(),
+        false,
+        hir::Unsafety::Normal,
+        Abi::Rust,
+    )))
+}
+
+/// Like `Env::re_early_bound`, but interned in the global `tcx`, which is
+/// what MIR passes work with.
+fn mk_re_early_bound<'a, 'tcx>(
+    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+    index: u32,
+    name: &'static str,
+) -> ty::Region<'tcx> {
+    tcx.mk_region(ty::ReEarlyBound(ty::EarlyBoundRegion {
+        def_id: tcx.hir().local_def_id(ast::CRATE_NODE_ID),
+        index,
+        name: Symbol::intern(name).as_interned_str(),
+    }))
+}
+
+/// Renames every use of the local `from` to `to`.
+struct RenameLocal {
+    from: Local,
+    to: Local,
+}
+
+impl<'tcx> MutVisitor<'tcx> for RenameLocal {
+    fn visit_local(&mut self, local: &mut Local, _: PlaceContext<'tcx>, _: Location) {
+        if *local == self.from {
+            *local = self.to;
+        }
+    }
+}
+
+/// Leaves everything as it is, for running along with `EraseRegions`.
+struct DoNothing;
+
+impl<'tcx> MutVisitor<'tcx> for DoNothing {}
+
+/// Builds the MIR most of the tests below start from, with a local for each
+/// kind of region `EraseRegions` treats differently:
+///
+/// - `_1: &'a u32`, with a user type annotation saying so,
+/// - `_2: &'static u32`,
+/// - `_3: for<'r> fn(&'r u32)`, whose late-bound region is anonymized already,
+/// - `_4: u32`, which is what all of them borrow,
+/// - `_5: &'b u32`,
+///
+/// and the borrows `_1 = &'a _4` and `_2 = &'static _4` in `bb0` and
+/// `_5 = &'b _4` in `bb1`.
+fn mk_fixture<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>) -> Mir<'tcx> {
+    let re_a = mk_re_early_bound(tcx, 0, "'a");
+    let re_b = mk_re_early_bound(tcx, 1, "'b");
+    let re_anon = tcx.mk_region(ty::ReLateBound(ty::INNERMOST, ty::BrAnon(1)));
+    let local_tys = [
+        tcx.mk_imm_ref(re_a, tcx.types.u32),
+        tcx.mk_imm_ref(tcx.types.re_static, tcx.types.u32),
+        mk_fn_taking_ref(tcx, re_anon),
+        tcx.types.u32,
+        tcx.mk_imm_ref(re_b, tcx.types.u32),
+    ];
+    let blocks = vec![
+        vec![mk_borrow(1, re_a, 4), mk_borrow(2, tcx.types.re_static, 4)],
+        vec![mk_borrow(5, re_b, 4)],
+    ];
+    let mut mir = mk_mir(tcx, &local_tys, blocks);
+    mir.local_decls[Local::new(1)].user_ty =
+        UserTypeProjections::from_projections(iter::once((fixture_user_ty(tcx), DUMMY_SP)));
+    mir
+}
+
+/// The user type annotation of `_1` in `mk_fixture`, which `EraseRegions`
+/// keeps as it is.
+fn fixture_user_ty<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>) -> UserTypeProjection<'tcx> {
+    UserTypeProjection {
+        base: UserTypeAnnotation::Ty(Canonical {
+            max_universe: ty::UniverseIndex::ROOT,
+            variables: ty::List::empty(),
+            value: tcx.mk_imm_ref(mk_re_early_bound(tcx, 0, "'a"), tcx.types.u32),
+        }),
+        projs: vec![],
+    }
+}
+
+/// The locals of `mk_fixture` whose type has regions.
+const FIXTURE_LOCALS: &[usize] = &[1, 2, 3, 5];
+
+/// The block and statement index of each borrow in `mk_fixture`.
+const FIXTURE_BORROWS: &[(usize, usize)] = &[(0, 0), (0, 1), (1, 0)];
+
+/// The locals of `mk_fixture` whose type has free regions, which is all of
+/// `FIXTURE_LOCALS` except `_3`.
+const FREE_REGION_LOCALS: &[usize] = &[1, 2, 5];
+
+/// The ways to erase the regions of a MIR body in place.
+#[derive(Copy, Clone, Debug)]
+enum EntryPoint {
+    InMir,
+    InBlock(usize),
+    ForLocals(&'static [usize]),
+    DryRun,
+    ForTest,
+    With,
+    LateBoundOnly,
+    Recording,
+    KeepingShadow,
+    KeepingStatic,
+    RunPass,
+}
+
+impl EntryPoint {
+    fn run<'a, 'tcx>(self, tcx: TyCtxt<'a, 'tcx, 'tcx>, mir: &mut Mir<'tcx>) {
+        match self {
+            EntryPoint::InMir => *mir = erase_regions::erase_regions_in_mir(tcx, mir),
+            EntryPoint::InBlock(block) => {
+                let block = BasicBlock::new(block);
+                let data = &mut mir.basic_blocks_mut()[block];
+                erase_regions::erase_regions_in_block(tcx, block, data);
+            }
+            EntryPoint::ForLocals(locals) => {
+                let locals = locals.iter().map(|&local| Local::new(local)).collect();
+                erase_regions::erase_regions_for_locals(tcx, mir, locals);
+            }
+            EntryPoint::DryRun => {
+                erase_regions::erase_regions_dry_run(tcx, mir);
+            }
+            EntryPoint::ForTest => erase_regions::erase_regions_for_test(tcx, mir),
+            EntryPoint::With => {
+                erase_regions::erase_regions_with(tcx, mir, DoNothing);
+            }
+            EntryPoint::LateBoundOnly => erase_regions::erase_late_bound_regions_in_mir(tcx, mir),
+            EntryPoint::Recording => {
+                erase_regions::erase_regions_recording(tcx, mir);
+            }
+            EntryPoint::KeepingShadow => {
+                erase_regions::erase_regions_keeping_shadow(tcx, mir);
+            }
+            EntryPoint::KeepingStatic => erase_regions::erase_regions_keeping_static(tcx, mir),
+            EntryPoint::RunPass => {
+                let source = MirSource::item(tcx.hir().local_def_id(ast::CRATE_NODE_ID));
+                erase_regions::run_erase_regions(tcx, source, mir);
+            }
+        }
+    }
+}
+
+/// Each entry point, with the locals whose types and the borrows whose
+/// regions it changes in `mk_fixture`.
+const ENTRY_POINTS: &[(EntryPoint, &[usize], &[(usize, usize)])] = &[
+    (EntryPoint::InMir, FREE_REGION_LOCALS, FIXTURE_BORROWS),
+    (EntryPoint::InBlock(1), &[], &[(1, 0)]),
+    (EntryPoint::ForLocals(&[1, 4]), &[1], &[(0, 0)]),
+    (EntryPoint::DryRun, &[], &[]),
+    (EntryPoint::ForTest, FREE_REGION_LOCALS, FIXTURE_BORROWS),
+    (EntryPoint::With, FREE_REGION_LOCALS, FIXTURE_BORROWS),
+    (EntryPoint::LateBoundOnly, &[3], &[]),
+    (EntryPoint::Recording, FREE_REGION_LOCALS, FIXTURE_BORROWS),
+    (EntryPoint::KeepingShadow, FREE_REGION_LOCALS, FIXTURE_BORROWS),
+    (EntryPoint::KeepingStatic, &[1, 5], &[(0, 0), (1, 0)]),
+    (EntryPoint::RunPass, FREE_REGION_LOCALS, FIXTURE_BORROWS),
+];
+
+#[test]
+fn entry_points_erase_what_they_should() {
+    test_env(EMPTY_SOURCE_STR, errors(&[]), |env| {
+        let tcx = env.infcx.tcx.global_tcx();
+        let fixture = mk_fixture(tcx);
+        for &(entry_point, changed_locals, changed_borrows) in ENTRY_POINTS {
+            let mut mir = fixture.clone();
+            entry_point.run(tcx, &mut mir);
+
+            for &local in FIXTURE_LOCALS {
+                let before = fixture.local_decls[Local::new(local)].ty;
+                let after = mir.local_decls[Local::new(local)].ty;
+                assert_eq!(
+                    after != before,
+                    changed_locals.contains(&local),
+                    "{:?} left _{}: `{:?}` as `{:?}`",
+                    entry_point,
+                    local,
+                    before,
+                    after
+                );
+            }
+            for &(block, index) in FIXTURE_BORROWS {
+                let expected = if changed_borrows.contains(&(block, index)) {
+                    tcx.types.re_erased
+                } else {
+                    borrow_region(&fixture, block, index)
+                };
+                assert_eq!(
+                    borrow_region(&mir, block, index),
+                    expected,
+                    "{:?} at bb{}[{}]",
+                    entry_point,
+                    block,
+                    index
+                );
+            }
+            assert_eq!(
+                mir.local_decls[Local::new(1)].user_ty.projections().collect::<Vec<_>>(),
+                [&fixture_user_ty(tcx)],
+                "{:?} changed the user type annotation",
+                entry_point
+            );
+        }
+    })
+}
+
+#[test]
+fn side_tables_describe_the_erasure() {
+    test_env(EMPTY_SOURCE_STR, errors(&[]), |env| {
+        let tcx = env.infcx.tcx.global_tcx();
+        let fixture = mk_fixture(tcx);
+        let borrows: Vec<_> = FIXTURE_BORROWS
+            .iter()
+            .map(|&(block, statement_index)| Location {
+                block: BasicBlock::new(block),
+                statement_index,
+            })
+            .collect();
+
+        let report = erase_regions::erase_regions_dry_run(tcx, &mut fixture.clone());
+        let reported = report.iter().map(|&(location, _)| location).collect::<FxHashSet<_>>();
+        assert_eq!(report.len(), FIXTURE_BORROWS.len());
+        assert_eq!(reported, borrows.iter().cloned().collect());
+
+        let rename = || RenameLocal {
+            from: Local::new(4),
+            to: Local::new(2),
+        };
+        let mut composed = fixture.clone();
+        erase_regions::erase_regions_with(tcx, &mut composed, rename());
+        let mut sequential = fixture.clone();
+        erase_regions::erase_regions_for_test(tcx, &mut sequential);
+        rename().visit_mir(&mut sequential);
+        assert_eq!(
+            format!("{:?}", composed.basic_blocks()),
+            format!("{:?}", sequential.basic_blocks())
+        );
+        for (composed, sequential) in composed.local_decls.iter().zip(&sequential.local_decls) {
+            assert_eq!(composed.ty, sequential.ty);
+        }
+
+        let recorded = erase_regions::erase_regions_recording(tcx, &mut fixture.clone());
+        assert_eq!(recorded.len(), FIXTURE_BORROWS.len());
+        for (location, &(block, index)) in borrows.iter().zip(FIXTURE_BORROWS) {
+            assert_eq!(recorded[location], [borrow_region(&fixture, block, index)]);
+        }
+
+        // `_1`, `_2` and `_5` are all erased to `&u32`; `_1` comes first.
+        let shadow = erase_regions::erase_regions_keeping_shadow(tcx, &mut fixture.clone());
+        let erased = tcx.mk_imm_ref(tcx.types.re_erased, tcx.types.u32);
+        assert_eq!(shadow.len(), 1);
+        assert_eq!(shadow[&erased], fixture.local_decls[Local::new(1)].ty);
+    })
+}
+
+#[test]
+fn region_eraser_erases_like_erase_regions() {
+    test_env(EMPTY_SOURCE_STR, errors(&[]), |env| {
+        let tcx = env.infcx.tcx.global_tcx();
+        let re_a = mk_re_early_bound(tcx, 0, "'a");
+        let re_b = mk_re_early_bound(tcx, 1, "'b");
+
+        let ref_b = tcx.mk_imm_ref(re_b, tcx.types.u32);
+        let ty = tcx.mk_imm_ref(re_a, tcx.intern_tup(&[tcx.types.u32, ref_b]));
+        let mut repeated = vec![ty; 100];
+        repeated.push(ref_b);
+        let mut eraser = erase_regions::RegionEraser::new(tcx);
+        eraser.erase_slice(&mut repeated);
+        assert_eq!(eraser.cached_tys(), 2);
+        assert!(repeated[..100].iter().all(|&erased| erased == tcx.erase_regions(&ty)));
+
+        let eraser = erase_regions::RegionEraser::new(tcx);
+        let integral = ty::Const::from_usize(tcx, 5);
+        assert!(ptr::eq(eraser.erase_const(integral), integral));
+        let array_of_ref = |region| tcx.mk_array(tcx.mk_imm_ref(region, tcx.types.u32), 0);
+        let with_ref = ty::Const::zero_sized(tcx, array_of_ref(re_a));
+        let erased = eraser.erase_const(with_ref);
+        assert_eq!(erased.ty, array_of_ref(tcx.types.re_erased));
+        assert_eq!(erased.val, with_ref.val);
+
+        let re_late = tcx.mk_region(ty::ReLateBound(ty::INNERMOST, ty::BrAnon(0)));
+        let parts = [
+            tcx.mk_imm_ref(re_a, tcx.types.u32),
+            tcx.mk_imm_ref(re_b, tcx.types.u32),
+            tcx.types.u32,
+            mk_fn_taking_ref(tcx, re_late),
+            tcx.mk_array(tcx.mk_array(tcx.mk_imm_ref(re_a, tcx.types.u32), 2), 3),
+        ];
+        // Many of these share parts, so the shared cache gets hits.
+        let tys: Vec<_> = (0..50)
+            .map(|i| {
+                let ty = parts[i % parts.len()];
+                match i % 3 {
+                    0 => ty,
+                    1 => tcx.intern_tup(&[ty, parts[(i / 3) % parts.len()]]),
+                    _ => tcx.mk_array(ty, (i % 4) as u64),
+                }
+            })
+            .collect();
+        let mut erased = tys.clone();
+        erase_regions::RegionEraser::new(tcx).erase_slice(&mut erased);
+        for (&ty, &erased_ty) in tys.iter().zip(&erased) {
+            assert!(ptr::eq(erased_ty, tcx.erase_regions(&ty)));
+            assert_eq!(erased_ty, erase_regions::RegionEraser::new(tcx).erase_ty(ty));
+        }
+    })
+}
+
+#[test]
+fn pass_reports_what_it_changed() {
+    test_env(EMPTY_SOURCE_STR, errors(&[]), |env| {
+        let tcx = env.infcx.tcx.global_tcx();
+        let def_id = tcx.hir().local_def_id(ast::CRATE_NODE_ID);
+        let source = MirSource::item(def_id);
+        let pass = erase_regions::EraseRegions;
+
+        // Once its late-bound region is anonymized, `fn(&'x u32)` is left
+        // alone, without even interning anything.
+        let name = Symbol::intern("'x").as_interned_str();
+        let re_x = tcx.mk_region(ty::ReLateBound(ty::INNERMOST, ty::BrNamed(def_id, name)));
+        let mut mir = mk_mir(tcx, &[mk_fn_taking_ref(tcx, re_x)], vec![vec![]]);
+        assert!(pass.run_pass_with_result(tcx, source, &mut mir).changed);
+        let interned_bytes = tcx.interned_bytes();
+        assert!(!pass.run_pass_with_result(tcx, source, &mut mir).changed);
+        assert_eq!(tcx.interned_bytes(), interned_bytes);
+        let mut eraser = erase_regions::RegionEraser::new(tcx);
+        eraser.erase_ty(mir.local_decls[Local::new(1)].ty);
+        assert_eq!(eraser.cached_tys(), 0);
+
+        // Only what changed is counted, so neither `_3` nor `_4`.
+        let stats = erase_regions::EraseRegions::erase_with_stats(tcx, &mut mk_fixture(tcx));
+        let expected = erase_regions::EraseRegionsStats {
+            regions: 3,
+            tys: 3,
+            substs: 0,
+            consts: 0,
+        };
+        assert_eq!(stats, expected);
+
+        let re_a = mk_re_early_bound(tcx, 0, "'a");
+        let ref_a = tcx.mk_imm_ref(re_a, tcx.types.u32);
+        let bodies: Vec<_> = (1..5)
+            .map(|n| {
+                let blocks = vec![(0..n).map(|_| mk_borrow(1, re_a, 2)).collect()];
+                mk_mir(tcx, &[ref_a, tcx.types.u32], blocks)
+            })
+            .collect();
+        let (mut regions, mut tys) = (0, 0);
+        for mir in &bodies {
+            let stats = erase_regions::EraseRegions::erase_with_stats(tcx, &mut mir.clone());
+            regions += stats.regions;
+            tys += stats.tys;
+        }
+        let perf_stats = &tcx.sess.perf_stats;
+        let regions_before = perf_stats.erased_regions.load(Ordering::Relaxed);
+        let tys_before = perf_stats.erased_tys.load(Ordering::Relaxed);
+        sync::par_iter(bodies).for_each(|mut mir| {
+            erase_regions::run_erase_regions(tcx, source, &mut mir);
+        });
+        assert_eq!(regions, 1 + 2 + 3 + 4);
+        assert_eq!(perf_stats.erased_regions.load(Ordering::Relaxed) - regions_before, regions);
+        assert_eq!(perf_stats.erased_tys.load(Ordering::Relaxed) - tys_before, tys);
+
+        let mut fixture = mk_fixture(tcx);
+        assert!(pass.run_pass_with_result(tcx, source, &mut fixture).changed);
+        assert!(!pass.run_pass_with_result(tcx, source, &mut fixture).changed);
+    })
+}
+
+#[test]
+fn remove_nops_keeps_the_other_statements_in_order() {
+    test_env(EMPTY_SOURCE_STR, errors(&[]), |env| {
+        let tcx = env.infcx.tcx.global_tcx();
+        let re_a = mk_re_early_bound(tcx, 0, "'a");
+        let ref_a = tcx.mk_imm_ref(re_a, tcx.types.u32);
+        let local_tys = [ref_a, ref_a, tcx.types.u32];
+        let blocks = vec![
+            vec![
+                StatementKind::Nop,
+                mk_borrow(1, re_a, 3),
+                StatementKind::Nop,
+                StatementKind::Nop,
+                mk_borrow(2, re_a, 3),
+            ],
+            vec![StatementKind::Nop],
+        ];
+        let mut mir = mk_mir(tcx, &local_tys, blocks);
+
+        remove_nops::remove_nops(mir.basic_blocks_mut());
+
+        let blocks = vec![vec![mk_borrow(1, re_a, 3), mk_borrow(2, re_a, 3)], vec![]];
+        let expected = mk_mir(tcx, &local_tys, blocks);
+        assert_eq!(
+            format!("{:?}", mir.basic_blocks()),
+            format!("{:?}", expected.basic_blocks())
+        );
+    })
+}
+
+#[test]
+#[should_panic(expected = "has a region other than the interned `ReErased`")]
+fn assert_all_regions_erased_rejects_a_re_erased_of_its_own() {
+    test_env(EMPTY_SOURCE_STR, errors(&[]), |env| {
+        let tcx = env.infcx.tcx.global_tcx();
+        let mut mir = mk_fixture(tcx);
+        erase_regions::erase_regions_for_test(tcx, &mut mir);
+        check_regions_erased::assert_all_regions_erased(tcx, &mir);
+
+        // Equal to `tcx.types.re_erased`, but not the interned one.
+        let statement = &mut mir.basic_blocks_mut()[BasicBlock::new(1)].statements[0];
+        statement.kind = mk_borrow(5, &ty::ReErased, 4);
+        check_regions_erased::assert_all_regions_erased(tcx, &mir);
+    })
+}
+
+#[test]
+fn erased_mir_survives_a_round_trip() {
+    test_env(EMPTY_SOURCE_STR, errors(&[]), |env| {
+        let tcx = env.infcx.tcx.global_tcx();
+        let mut mir = mk_fixture(tcx);
+        erase_regions::erase_regions_for_test(tcx, &mut mir);
+
+        check_regions_erased::check_erasure_round_trip(tcx, &mir);
+    })
+}
+
+#[test]
+fn unerase_regions_names_every_erased_region() {
+    test_env(EMPTY_SOURCE_STR, errors(&[]), |env| {
+        let tcx = env.infcx.tcx.global_tcx();
+        let def_id = tcx.hir().local_def_id(ast::CRATE_NODE_ID);
+        let mut erased = mk_fixture(tcx);
+        erase_regions::erase_regions_for_test(tcx, &mut erased);
+        let mut unerased = erased.clone();
+
+        unerase_regions::unerase_regions(tcx, def_id, &mut unerased);
+
+        let re_u0 = tcx.mk_region(ty::ReFree(ty::FreeRegion {
+            scope: def_id,
+            bound_region: ty::BrNamed(def_id, Symbol::intern("'u0").as_interned_str()),
+        }));
+        assert_eq!(borrow_region(&unerased, 0, 0), re_u0);
+        for &(block, index) in FIXTURE_BORROWS {
+            assert_ne!(borrow_region(&unerased, block, index), tcx.types.re_erased);
+        }
+        for local_decl in &unerased.local_decls {
+            assert!(!tcx.any_free_region_meets(&local_decl.ty, |region| *region == ty::ReErased));
+        }
+
+        let mut again = unerased;
+        erase_regions::erase_regions_for_test(tcx, &mut again);
+        assert_eq!(
+            format!("{:?}", again.basic_blocks()),
+            format!("{:?}", erased.basic_blocks())
+        );
+        for (again, erased) in again.local_decls.iter().zip(&erased.local_decls) {
+            assert_eq!(again.ty, erased.ty);
+        }
+    })
+}
+
+#[test]
+fn regions_left_in_the_signature_delay_a_bug() {
+    // Print the delayed bug right away, so that `errors` sees it.
+    let mut options = test_options();
+    options.debugging_opts.report_delayed_bugs = true;
+    let args = errors(&["EraseRegions left regions in"]);
+    test_env_with_options(options, EMPTY_SOURCE_STR, args, |env| {
+        let tcx = env.infcx.tcx.global_tcx();
+        let def_id = tcx.hir().local_def_id(ast::CRATE_NODE_ID);
+        let mut mir = mk_fixture(tcx);
+        // Only the return place and the arguments are checked.
+        assert!(!erase_regions::report_regions_left_in_signature(tcx, def_id, &mir));
+
+        let re_a = mk_re_early_bound(tcx, 0, "'a");
+        mir.local_decls[RETURN_PLACE].ty = tcx.mk_imm_ref(re_a, tcx.types.u32);
+        assert!(erase_regions::report_regions_left_in_signature(tcx, def_id, &mir));
+    })
+}
+
+#[test]
+fn is_mir_region_erased_checks_promoted() {
+    test_env(EMPTY_SOURCE_STR, errors(&[]), |env| {
+        let tcx = env.infcx.tcx.global_tcx();
+        let mut mir = mk_fixture(tcx);
+        assert!(!erase_regions::is_mir_region_erased(&mir));
+        erase_regions::erase_regions_for_test(tcx, &mut mir);
+        assert!(erase_regions::is_mir_region_erased(&mir));
+
+        mir.promoted.push(mk_fixture(tcx));
+        assert!(!erase_regions::is_mir_region_erased(&mir));
+        let erased = erase_regions::erase_regions_in_mir(tcx, &mir);
+        assert!(erase_regions::is_mir_region_erased(&erased));
+    })
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "EraseRegions must run after ElaborateDrops")]
+fn run_erase_regions_before_elaborate_drops() {
+    test_env(EMPTY_SOURCE_STR, errors(&[]), |env| {
+        let tcx = env.infcx.tcx.global_tcx();
+        let mut mir = mk_mir(tcx, &[tcx.types.u32], vec![vec![]]);
+        mir.phase = MirPhase::Validated;
+
+        let source = MirSource::item(tcx.hir().local_def_id(ast::CRATE_NODE_ID));
+        erase_regions::run_erase_regions(tcx, source, &mut mir);
+    })
+}
@@ -31,6 +31,8 @@ use syntax_pos::DUMMY_SP;
 use std::path::PathBuf;
 use std::sync::mpsc;
 
+mod mir_passes;
+
 struct Env<'a, 'gcx: 'a + 'tcx, 'tcx: 'a> {
     infcx: &'a infer::InferCtxt<'a, 'gcx, 'tcx>,
     region_scope_tree: &'a mut region::ScopeTree,
@@ -85,15 +87,29 @@ fn errors(msgs: &[&str]) -> (Box<dyn Emitter + sync::Send>, usize) {
     )
 }
 
+fn test_options() -> config::Options {
+    let mut options = config::Options::default();
+    options.debugging_opts.verbose = true;
+    options.unstable_features = UnstableFeatures::Allow;
+    options
+}
+
 fn test_env<F>(source_string: &str, args: (Box<dyn Emitter + sync::Send>, usize), body: F)
 where
     F: FnOnce(Env) + sync::Send,
 {
-    syntax::with_globals(|| {
-        let mut options = config::Options::default();
-        options.debugging_opts.verbose = true;
-        options.unstable_features = UnstableFeatures::Allow;
+    test_env_with_options(test_options(), source_string, args, body)
+}
 
+fn test_env_with_options<F>(
+    options: config::Options,
+    source_string: &str,
+    args: (Box<dyn Emitter + sync::Send>, usize),
+    body: F,
+) where
+    F: FnOnce(Env) + sync::Send,
+{
+    syntax::with_globals(|| {
         driver::spawn_thread_pool(options, |options| {
             test_env_with_pool(options, source_string, args, body)
         })
@@ -108,7 +124,14 @@ fn test_env_with_pool<F>(
 ) where
     F: FnOnce(Env),
 {
-    let diagnostic_handler = errors::Handler::with_emitter(true, false, emitter);
+    let diagnostic_handler = errors::Handler::with_emitter_and_flags(
+        emitter,
+        errors::HandlerFlags {
+            can_emit_warnings: true,
+            report_delayed_bugs: options.debugging_opts.report_delayed_bugs,
+            ..Default::default()
+        },
+    );
     let sess = session::build_session_(
         options,
         None,
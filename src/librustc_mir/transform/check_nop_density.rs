@@ -0,0 +1,55 @@
+//! Warns about MIR bodies that are mostly `Nop`s (`-Z warn-nop-density`).
+//!
+//! Passes that delete statements usually turn them into `Nop`s and leave them
+//! for `SimplifyCfg` to sweep away. This pass runs right before the final
+//! `SimplifyCfg`, and warns when more than half of the statements in a
+//! function are `Nop`s at that point. `SimplifyCfg` already swept away the
+//! ones left before drop elaboration, so these come from the optimizations,
+//! and point at one that deletes far more than it should. It never changes
+//! the MIR.
+
+use rustc::mir::*;
+use rustc::session::Session;
+use rustc::ty::TyCtxt;
+use transform::{MirPass, MirSource};
+
+/// The fraction of `Nop` statements above which a body is warned about.
+const MAX_NOP_DENSITY: f64 = 0.5;
+
+pub struct CheckNopDensity;
+
+impl MirPass for CheckNopDensity {
+    fn is_enabled(&self, sess: &Session) -> bool {
+        sess.opts.debugging_opts.warn_nop_density
+    }
+
+    fn run_pass<'a, 'tcx>(&self,
+                          tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                          source: MirSource,
+                          mir: &mut Mir<'tcx>) {
+        // Promoted MIR is warned about as part of the function it is in.
+        if source.promoted.is_some() {
+            return;
+        }
+        let (nops, statements) = count_nops(mir);
+        if statements > 0 && nops as f64 / statements as f64 > MAX_NOP_DENSITY {
+            tcx.sess.span_warn(tcx.def_span(source.def_id), &format!(
+                "{} of the {} MIR statements are `Nop`s", nops, statements));
+        }
+    }
+}
+
+/// Returns the number of `Nop` statements in `mir`, and the number of all
+/// statements.
+pub fn count_nops(mir: &Mir) -> (usize, usize) {
+    let mut nops = 0;
+    let mut statements = 0;
+    for data in mir.basic_blocks() {
+        statements += data.statements.len();
+        nops += data.statements.iter().filter(|statement| match statement.kind {
+            StatementKind::Nop => true,
+            _ => false,
+        }).count();
+    }
+    (nops, statements)
+}
@@ -0,0 +1,305 @@
+//! Checks that `EraseRegions` left no erasable regions behind (`-Z verify-erased`).
+//!
+//! Codegen assumes that all regions are gone, and panics far from the cause
+//! when one is not. This pass instead reports every type, region, constant
+//! and substs that still has a region as a compiler bug, pointing at the
+//! local declaration, statement or terminator it occurs in. It never changes
+//! the MIR.
+//!
+//! Regions of the kinds codegen cannot handle at all, e.g. `ReFree` or
+//! `ReVar`, are also reported separately, along with their kind, by
+//! `unexpected_regions`.
+//!
+//! If no type has regions left, it goes on to `assert_all_regions_erased`, to
+//! also catch regions that were erased to something other than the interned
+//! `ReErased`, and to `check_erasure_round_trip`.
+
+use rustc::mir::*;
+use rustc::mir::visit::{TyContext, Visitor};
+use rustc::session::Session;
+use rustc::ty::subst::Substs;
+use rustc::ty::{self, Ty, TyCtxt, TypeFoldable};
+use rustc::ty::fold::TypeFolder;
+use syntax_pos::Span;
+use transform::{MirPass, MirSource};
+use transform::erase_regions::erase_regions_in_mir;
+
+use std::fmt::Debug;
+use std::ptr;
+
+pub struct CheckRegionsErased;
+
+impl MirPass for CheckRegionsErased {
+    fn is_enabled(&self, sess: &Session) -> bool {
+        sess.opts.debugging_opts.verify_erased
+    }
+
+    fn run_pass<'a, 'tcx>(&self,
+                          tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                          source: MirSource,
+                          mir: &mut Mir<'tcx>) {
+        // Both checks report through the same path, right away, so that a
+        // region is reported even if a later check panics.
+        let handler = tcx.sess.diagnostic();
+        for (span, region) in unexpected_regions(tcx, mir) {
+            handler.span_bug_no_panic(span, &format!(
+                "the {} in {:?} survived EraseRegions", region, source.def_id));
+        }
+        let regions_left = regions_left(mir);
+        for &(span, ref value) in &regions_left {
+            handler.span_bug_no_panic(span, &format!(
+                "the {} in {:?} still has regions after EraseRegions", value, source.def_id));
+        }
+        if regions_left.is_empty() {
+            assert_all_regions_erased(tcx, mir);
+            check_erasure_round_trip(tcx, mir);
+        }
+    }
+}
+
+/// Where a type visited in `context` occurs in `mir`, as a span and as a
+/// phrase to put after the type in a report.
+fn describe_ty_context(mir: &Mir, context: TyContext) -> (Span, String) {
+    match context {
+        TyContext::LocalDecl { local, source_info } => {
+            (source_info.span, format!("of {:?}", local))
+        }
+        TyContext::ReturnTy(source_info) => (source_info.span, "of the return place".to_string()),
+        TyContext::YieldTy(source_info) => (source_info.span, "yielded".to_string()),
+        TyContext::Location(location) => {
+            (mir.source_info(location).span, format!("at {:?}", location))
+        }
+    }
+}
+
+/// Returns every `ReEarlyBound`, `ReFree`, `ReScope` or `ReVar` region in
+/// `mir`, described along with where it occurs. These can only come from
+/// type checking and borrow checking, and codegen cannot handle them.
+pub fn unexpected_regions<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                    mir: &Mir<'tcx>)
+                                    -> Vec<(Span, String)> {
+    let mut collector = UnexpectedRegions { tcx, mir, found: vec![] };
+    collector.visit_mir(mir);
+    collector.found
+}
+
+fn region_kind_name(region: ty::Region) -> &'static str {
+    match *region {
+        ty::ReEarlyBound(..) => "early-bound",
+        ty::ReFree(..) => "free",
+        ty::ReScope(..) => "scope",
+        ty::ReVar(..) => "inference",
+        _ => "unexpected",
+    }
+}
+
+struct UnexpectedRegions<'b, 'a: 'b, 'tcx: 'a> {
+    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+    mir: &'b Mir<'tcx>,
+    found: Vec<(Span, String)>,
+}
+
+impl<'b, 'a, 'tcx> UnexpectedRegions<'b, 'a, 'tcx> {
+    fn check<T: TypeFoldable<'tcx>>(&mut self, value: &T, span: Span, place: &str) {
+        let found = &mut self.found;
+        self.tcx.for_each_free_region(value, |region| match *region {
+            ty::ReEarlyBound(..) | ty::ReFree(..) | ty::ReScope(..) | ty::ReVar(..) => {
+                found.push((span, format!("{} region `{:?}` {}",
+                                          region_kind_name(region), region, place)));
+            }
+            _ => {}
+        });
+    }
+
+    fn check_at<T: TypeFoldable<'tcx>>(&mut self, value: &T, location: Location) {
+        let span = self.mir.source_info(location).span;
+        self.check(value, span, &format!("at {:?}", location));
+    }
+}
+
+impl<'b, 'a, 'tcx> Visitor<'tcx> for UnexpectedRegions<'b, 'a, 'tcx> {
+    fn visit_ty(&mut self, ty: &Ty<'tcx>, context: TyContext) {
+        let (span, place) = describe_ty_context(self.mir, context);
+        self.check(ty, span, &place);
+    }
+
+    fn visit_region(&mut self, region: &ty::Region<'tcx>, location: Location) {
+        self.check_at(region, location);
+    }
+
+    fn visit_const(&mut self, constant: &&'tcx ty::Const<'tcx>, location: Location) {
+        self.check_at(constant, location);
+    }
+
+    fn visit_substs(&mut self, substs: &&'tcx Substs<'tcx>, location: Location) {
+        self.check_at(substs, location);
+    }
+
+    fn visit_closure_substs(&mut self, substs: &ty::ClosureSubsts<'tcx>, location: Location) {
+        self.check_at(substs, location);
+    }
+
+    fn visit_generator_substs(&mut self,
+                              substs: &ty::GeneratorSubsts<'tcx>,
+                              location: Location) {
+        self.check_at(substs, location);
+    }
+}
+
+/// Panics unless every free region in `mir` is `tcx.types.re_erased` itself,
+/// rather than just another `ReErased` that compares equal to it. Regions
+/// made with `tcx.mk_region` are interned, so they always pass; this catches
+/// the ones that were not, e.g. a `&ty::ReErased` that a pass promoted to a
+/// constant of its own, which code comparing interned values by address
+/// tells apart from the interned one. Late-bound regions, which
+/// `EraseRegions` keeps, are not checked.
+pub fn assert_all_regions_erased<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, mir: &Mir<'tcx>) {
+    NonCanonicalRegions { tcx }.visit_mir(mir);
+}
+
+struct NonCanonicalRegions<'a, 'tcx: 'a> {
+    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+}
+
+impl<'a, 'tcx> NonCanonicalRegions<'a, 'tcx> {
+    fn check<T: TypeFoldable<'tcx> + Debug>(&self, value: &T) {
+        let re_erased = self.tcx.types.re_erased;
+        if self.tcx.any_free_region_meets(value, |region| !ptr::eq(region, re_erased)) {
+            bug!("`{:?}` has a region other than the interned `ReErased`", value);
+        }
+    }
+}
+
+impl<'a, 'tcx> Visitor<'tcx> for NonCanonicalRegions<'a, 'tcx> {
+    fn visit_ty(&mut self, ty: &Ty<'tcx>, _: TyContext) {
+        self.check(ty);
+    }
+
+    fn visit_region(&mut self, region: &ty::Region<'tcx>, _: Location) {
+        self.check(region);
+    }
+
+    fn visit_const(&mut self, constant: &&'tcx ty::Const<'tcx>, _: Location) {
+        self.check(constant);
+    }
+
+    fn visit_substs(&mut self, substs: &&'tcx Substs<'tcx>, _: Location) {
+        self.check(substs);
+    }
+
+    fn visit_closure_substs(&mut self, substs: &ty::ClosureSubsts<'tcx>, _: Location) {
+        self.check(substs);
+    }
+
+    fn visit_generator_substs(&mut self, substs: &ty::GeneratorSubsts<'tcx>, _: Location) {
+        self.check(substs);
+    }
+}
+
+/// Panics unless erasing regions in the already erased `mir` again, after
+/// rebuilding each of its types and regions from scratch, gives back the very
+/// same interned types. This is what encoding the MIR into metadata and
+/// decoding it in another crate does, so a mismatch means that the interner
+/// or `erase_regions` does not produce canonical results.
+pub fn check_erasure_round_trip<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, mir: &Mir<'tcx>) {
+    let rebuilt = mir.fold_with(&mut Reinterner { tcx });
+    let round_tripped = erase_regions_in_mir(tcx, &rebuilt);
+
+    let (mut before, mut after) = (CollectTys(vec![]), CollectTys(vec![]));
+    before.visit_mir(mir);
+    after.visit_mir(&round_tripped);
+    assert_eq!(before.0.len(), after.0.len(),
+               "the round trip changed the number of types in the MIR");
+    for (&original, &round_tripped) in before.0.iter().zip(&after.0) {
+        if !ptr::eq(original, round_tripped) {
+            bug!("`{:?}` is not interned as `{:?}` after a round trip", round_tripped, original);
+        }
+    }
+}
+
+/// Interns every type and region anew, rather than reusing the pointers it
+/// is given.
+struct Reinterner<'a, 'tcx: 'a> {
+    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+}
+
+impl<'a, 'tcx> TypeFolder<'tcx, 'tcx> for Reinterner<'a, 'tcx> {
+    fn tcx<'b>(&'b self) -> TyCtxt<'b, 'tcx, 'tcx> {
+        self.tcx
+    }
+
+    fn fold_ty(&mut self, ty: Ty<'tcx>) -> Ty<'tcx> {
+        let folded = ty.super_fold_with(self);
+        self.tcx.mk_ty(folded.sty.clone())
+    }
+
+    fn fold_region(&mut self, region: ty::Region<'tcx>) -> ty::Region<'tcx> {
+        self.tcx.mk_region(*region)
+    }
+}
+
+/// Collects every type in a MIR body, in the order they are visited.
+pub struct CollectTys<'tcx>(pub Vec<Ty<'tcx>>);
+
+impl<'tcx> Visitor<'tcx> for CollectTys<'tcx> {
+    fn visit_ty(&mut self, ty: &Ty<'tcx>, _: TyContext) {
+        self.0.push(*ty);
+    }
+}
+
+/// Returns every type, region, constant and substs in `mir` that still has
+/// erasable regions, described along with where it occurs. This covers the
+/// local declarations as well as the statements and terminators.
+pub fn regions_left<'tcx>(mir: &Mir<'tcx>) -> Vec<(Span, String)> {
+    let mut collector = RegionsLeft { mir, found: vec![] };
+    collector.visit_mir(mir);
+    collector.found
+}
+
+struct RegionsLeft<'b, 'tcx: 'b> {
+    mir: &'b Mir<'tcx>,
+    found: Vec<(Span, String)>,
+}
+
+impl<'b, 'tcx> RegionsLeft<'b, 'tcx> {
+    fn check_at<T: TypeFoldable<'tcx> + Debug>(&mut self,
+                                               what: &str,
+                                               value: &T,
+                                               location: Location) {
+        if value.has_erasable_regions() {
+            let span = self.mir.source_info(location).span;
+            self.found.push((span, format!("{} `{:?}` at {:?}", what, value, location)));
+        }
+    }
+}
+
+impl<'b, 'tcx> Visitor<'tcx> for RegionsLeft<'b, 'tcx> {
+    fn visit_ty(&mut self, ty: &Ty<'tcx>, context: TyContext) {
+        if ty.has_erasable_regions() {
+            let (span, place) = describe_ty_context(self.mir, context);
+            self.found.push((span, format!("type `{:?}` {}", ty, place)));
+        }
+    }
+
+    fn visit_region(&mut self, region: &ty::Region<'tcx>, location: Location) {
+        self.check_at("region", region, location);
+    }
+
+    fn visit_const(&mut self, constant: &&'tcx ty::Const<'tcx>, location: Location) {
+        self.check_at("constant", constant, location);
+    }
+
+    fn visit_substs(&mut self, substs: &&'tcx Substs<'tcx>, location: Location) {
+        self.check_at("substs", substs, location);
+    }
+
+    fn visit_closure_substs(&mut self, substs: &ty::ClosureSubsts<'tcx>, location: Location) {
+        self.check_at("closure substs", substs, location);
+    }
+
+    fn visit_generator_substs(&mut self,
+                              substs: &ty::GeneratorSubsts<'tcx>,
+                              location: Location) {
+        self.check_at("generator substs", substs, location);
+    }
+}
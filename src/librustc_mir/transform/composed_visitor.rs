@@ -0,0 +1,90 @@
+//! A `MutVisitor` that runs two other visitors in a single traversal.
+
+use rustc::hir::def_id::DefId;
+use rustc::mir::*;
+use rustc::mir::visit::{MutVisitor, PlaceContext, TyContext};
+use rustc::ty::subst::Substs;
+use rustc::ty::{self, ClosureSubsts, GeneratorSubsts, Ty};
+use syntax_pos::Span;
+
+/// Runs `A` and then `B` at every leaf of the MIR: types, regions, constants,
+/// substs, locals, spans, `DefId`s, branches and source scopes.
+///
+/// The structure of the MIR (blocks, statements, rvalues, places and so on)
+/// is walked only once, by `ComposedVisitor` itself. Any other `visit_*`
+/// method that `A` or `B` overrides is not called. Composing visitors only
+/// gives the same result as running them one after the other if they do all
+/// their work in the leaf methods.
+pub struct ComposedVisitor<A, B> {
+    pub a: A,
+    pub b: B,
+}
+
+impl<A, B> ComposedVisitor<A, B> {
+    pub fn new(a: A, b: B) -> Self {
+        ComposedVisitor { a, b }
+    }
+
+    pub fn into_inner(self) -> (A, B) {
+        (self.a, self.b)
+    }
+}
+
+impl<'tcx, A: MutVisitor<'tcx>, B: MutVisitor<'tcx>> MutVisitor<'tcx> for ComposedVisitor<A, B> {
+    fn visit_branch(&mut self, source: BasicBlock, target: BasicBlock) {
+        self.a.visit_branch(source, target);
+        self.b.visit_branch(source, target);
+    }
+
+    fn visit_def_id(&mut self, def_id: &mut DefId, location: Location) {
+        self.a.visit_def_id(def_id, location);
+        self.b.visit_def_id(def_id, location);
+    }
+
+    fn visit_span(&mut self, span: &mut Span) {
+        self.a.visit_span(span);
+        self.b.visit_span(span);
+    }
+
+    fn visit_ty(&mut self, ty: &mut Ty<'tcx>, context: TyContext) {
+        self.a.visit_ty(ty, context);
+        self.b.visit_ty(ty, context);
+    }
+
+    fn visit_region(&mut self, region: &mut ty::Region<'tcx>, location: Location) {
+        self.a.visit_region(region, location);
+        self.b.visit_region(region, location);
+    }
+
+    fn visit_const(&mut self, constant: &mut &'tcx ty::Const<'tcx>, location: Location) {
+        self.a.visit_const(constant, location);
+        self.b.visit_const(constant, location);
+    }
+
+    fn visit_substs(&mut self, substs: &mut &'tcx Substs<'tcx>, location: Location) {
+        self.a.visit_substs(substs, location);
+        self.b.visit_substs(substs, location);
+    }
+
+    fn visit_closure_substs(&mut self, substs: &mut ClosureSubsts<'tcx>, location: Location) {
+        self.a.visit_closure_substs(substs, location);
+        self.b.visit_closure_substs(substs, location);
+    }
+
+    fn visit_generator_substs(&mut self,
+                              substs: &mut GeneratorSubsts<'tcx>,
+                              location: Location) {
+        self.a.visit_generator_substs(substs, location);
+        self.b.visit_generator_substs(substs, location);
+    }
+
+    fn visit_local(&mut self, local: &mut Local, context: PlaceContext<'tcx>, location: Location) {
+        self.a.visit_local(local, context, location);
+        self.b.visit_local(local, context, location);
+    }
+
+    fn visit_source_scope(&mut self, scope: &mut SourceScope) {
+        self.a.visit_source_scope(scope);
+        self.b.visit_source_scope(scope);
+    }
+}
@@ -0,0 +1,150 @@
+//! Folds integer arithmetic whose operands are both constants.
+//!
+//! Unlike `ConstProp`, this pass does not evaluate anything through the
+//! miri engine; it only looks at `Rvalue::BinaryOp`s of two integral
+//! `Operand::Constant`s and replaces them by the computed constant. An
+//! operation is left untouched whenever its checked counterpart would
+//! trap (overflow, or a shift by at least the bit width of the type).
+
+use rustc::mir::*;
+use rustc::mir::interpret::{sign_extend, truncate};
+use rustc::mir::visit::MutVisitor;
+use rustc::ty::{self, Ty, TyCtxt};
+use rustc::ty::layout::Size;
+use transform::{MirPass, MirSource};
+
+pub struct ConstFold;
+
+impl MirPass for ConstFold {
+    fn min_opt_level(&self) -> usize {
+        // We only run when the MIR optimization level is > 1.
+        // At the default level 1, LLVM folds this arithmetic anyway.
+        2
+    }
+
+    fn run_pass<'a, 'tcx>(&self,
+                          tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                          _: MirSource,
+                          mir: &mut Mir<'tcx>) {
+        ConstFoldVisitor { tcx }.visit_mir(mir);
+    }
+}
+
+struct ConstFoldVisitor<'a, 'tcx: 'a> {
+    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+}
+
+impl<'a, 'tcx> ConstFoldVisitor<'a, 'tcx> {
+    fn size_of(&self, ty: Ty<'tcx>) -> Option<Size> {
+        self.tcx.layout_of(ty::ParamEnv::reveal_all().and(ty)).ok().map(|layout| layout.size)
+    }
+
+    /// Reads the integral value of `constant`, returning its bits along
+    /// with the size and signedness of its type.
+    fn integral(&self, constant: &Constant<'tcx>) -> Option<(u128, Size, bool)> {
+        let signed = match constant.ty.sty {
+            ty::Int(_) => true,
+            ty::Uint(_) => false,
+            _ => return None,
+        };
+        let size = self.size_of(constant.ty)?;
+        let bits = constant.literal.val.try_to_bits(size)?;
+        Some((bits, size, signed))
+    }
+
+    fn fold_binary_op(&self,
+                      op: BinOp,
+                      lhs: &Constant<'tcx>,
+                      rhs: &Constant<'tcx>)
+                      -> Option<Constant<'tcx>> {
+        let (l, size, signed) = self.integral(lhs)?;
+        let (r, rhs_size, rhs_signed) = self.integral(rhs)?;
+        let is_shift = op == BinOp::Shl || op == BinOp::Shr;
+        if !is_shift && lhs.ty != rhs.ty {
+            return None;
+        }
+
+        let result = if is_shift {
+            // A shift by a negative amount, or by at least the bit width,
+            // would trap if it were checked.
+            let amount = if rhs_signed {
+                let amount = sign_extend(r, rhs_size) as i128;
+                if amount < 0 {
+                    return None;
+                }
+                amount as u128
+            } else {
+                r
+            };
+            if amount >= size.bits() as u128 {
+                return None;
+            }
+            let amount = amount as u32;
+            match (op, signed) {
+                (BinOp::Shl, _) => truncate(l << amount, size),
+                (_, true) => truncate(((sign_extend(l, size) as i128) >> amount) as u128, size),
+                (_, false) => l >> amount,
+            }
+        } else if signed {
+            let l = sign_extend(l, size) as i128;
+            let r = sign_extend(r, size) as i128;
+            let result = match op {
+                BinOp::Add => l.checked_add(r)?,
+                BinOp::Sub => l.checked_sub(r)?,
+                BinOp::Mul => l.checked_mul(r)?,
+                BinOp::BitAnd => l & r,
+                BinOp::BitOr => l | r,
+                BinOp::BitXor => l ^ r,
+                _ => return None,
+            } as u128;
+            // Check that the result is representable in the original type.
+            if sign_extend(truncate(result, size), size) != result {
+                return None;
+            }
+            truncate(result, size)
+        } else {
+            let result = match op {
+                BinOp::Add => l.checked_add(r)?,
+                BinOp::Sub => l.checked_sub(r)?,
+                BinOp::Mul => l.checked_mul(r)?,
+                BinOp::BitAnd => l & r,
+                BinOp::BitOr => l | r,
+                BinOp::BitXor => l ^ r,
+                _ => return None,
+            };
+            // Check that the result is representable in the original type.
+            if truncate(result, size) != result {
+                return None;
+            }
+            result
+        };
+
+        Some(Constant {
+            span: lhs.span,
+            ty: lhs.ty,
+            user_ty: None,
+            literal: ty::Const::from_bits(
+                self.tcx,
+                result,
+                ty::ParamEnv::reveal_all().and(lhs.ty),
+            ),
+        })
+    }
+}
+
+impl<'a, 'tcx> MutVisitor<'tcx> for ConstFoldVisitor<'a, 'tcx> {
+    fn visit_rvalue(&mut self, rvalue: &mut Rvalue<'tcx>, location: Location) {
+        let folded = match *rvalue {
+            Rvalue::BinaryOp(op, Operand::Constant(ref lhs), Operand::Constant(ref rhs)) => {
+                self.fold_binary_op(op, lhs, rhs)
+            }
+            _ => None,
+        };
+        if let Some(constant) = folded {
+            debug!("Folding {:?} into {:?}", rvalue, constant);
+            *rvalue = Rvalue::Use(Operand::Constant(box constant));
+        }
+
+        self.super_rvalue(rvalue, location)
+    }
+}
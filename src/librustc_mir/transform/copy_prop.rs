@@ -28,16 +28,16 @@ use util::def_use::DefUseAnalysis;
 pub struct CopyPropagation;
 
 impl MirPass for CopyPropagation {
-    fn run_pass<'a, 'tcx>(&self,
-                          tcx: TyCtxt<'a, 'tcx, 'tcx>,
-                          _source: MirSource,
-                          mir: &mut Mir<'tcx>) {
+    fn min_opt_level(&self) -> usize {
         // We only run when the MIR optimization level is > 1.
         // This avoids a slow pass, and messing up debug info.
-        if tcx.sess.opts.debugging_opts.mir_opt_level <= 1 {
-            return;
-        }
+        2
+    }
 
+    fn run_pass<'a, 'tcx>(&self,
+                          _tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                          _source: MirSource,
+                          mir: &mut Mir<'tcx>) {
         let mut def_use_analysis = DefUseAnalysis::new(mir);
         loop {
             def_use_analysis.analyze(mir);
@@ -79,6 +79,13 @@ impl MirPass for CopyPropagation {
                             dest_local);
                         continue;
                     }
+                    // Conservatively gives up if the address of the dest is taken,
+                    // because the reference may outlive the source.
+                    if dest_use_info.defs_and_uses.iter().any(|u| u.context.is_borrow()) {
+                        debug!("  Can't copy-propagate local: dest {:?} is borrowed",
+                            dest_local);
+                        continue;
+                    }
                     let dest_place_def = dest_use_info.defs_not_including_drop().next().unwrap();
                     location = dest_place_def.location;
 
@@ -0,0 +1,149 @@
+//! Dead store elimination.
+//!
+//! This removes assignments to locals that are never read afterwards, i.e.,
+//! that are overwritten (or go out of storage) on every path before any use,
+//! or that are never used again before the function returns.
+//!
+//! The pass is deliberately conservative:
+//!
+//! - the return place is never touched, since `Return` reads it implicitly;
+//! - a local that is borrowed anywhere in the function is never touched,
+//!   since the liveness analysis only sees uses at the point of the borrow and
+//!   not the later uses through the reference;
+//! - only assignments whose rvalue has no effect besides producing a value are
+//!   removed. In particular, borrows (which may alias) and moves (deleting
+//!   which would leak the moved value, as its drop was elaborated away) are
+//!   left alone.
+
+use rustc::mir::*;
+use rustc::mir::visit::{PlaceContext, Visitor};
+use rustc::ty::TyCtxt;
+use rustc_data_structures::bit_set::BitSet;
+use transform::{MirPass, MirSource};
+use util::liveness::{self, DefUse, IdentityMap, LiveVarSet};
+
+pub struct DeadStoreElimination;
+
+impl MirPass for DeadStoreElimination {
+    fn min_opt_level(&self) -> usize {
+        // We only run when the MIR optimization level is > 1.
+        // Removing assignments to user variables messes up debug info.
+        2
+    }
+
+    fn run_pass<'a, 'tcx>(&self,
+                          _tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                          _source: MirSource,
+                          mir: &mut Mir<'tcx>) {
+        let dead_stores = {
+            let mut borrowed = BorrowedLocals(BitSet::new_empty(mir.local_decls.len()));
+            borrowed.visit_mir(mir);
+            find_dead_stores(mir, &borrowed.0)
+        };
+
+        for location in dead_stores {
+            debug!("Deleting dead store at {:?}", location);
+            mir.make_statement_nop(location);
+        }
+    }
+}
+
+fn find_dead_stores<'tcx>(mir: &Mir<'tcx>, borrowed: &BitSet<Local>) -> Vec<Location> {
+    let liveness = liveness::liveness_of_locals(mir, &IdentityMap::new(mir));
+
+    let mut dead_stores = vec![];
+    for (block, data) in mir.basic_blocks().iter_enumerated() {
+        // Walk the block backwards, starting with the locals live on exit.
+        let mut live = liveness.outs[block].clone();
+        let mut location = Location { block, statement_index: data.statements.len() };
+        let mut defs_uses = DefsUses::default();
+        defs_uses.visit_terminator(block, data.terminator(), location);
+        defs_uses.apply(&mut live);
+
+        for (statement_index, statement) in data.statements.iter().enumerate().rev() {
+            location.statement_index = statement_index;
+            if let StatementKind::Assign(Place::Local(local), ref rvalue) = statement.kind {
+                if local != RETURN_PLACE &&
+                   !live.contains(local) &&
+                   !borrowed.contains(local) &&
+                   is_pure(rvalue) {
+                    // The statement goes away, so its uses must not make anything live.
+                    dead_stores.push(location);
+                    continue;
+                }
+            }
+            let mut defs_uses = DefsUses::default();
+            defs_uses.visit_statement(block, statement, location);
+            defs_uses.apply(&mut live);
+        }
+    }
+    dead_stores
+}
+
+/// Whether evaluating `rvalue` has no effect besides producing its value.
+fn is_pure<'tcx>(rvalue: &Rvalue<'tcx>) -> bool {
+    let is_pure_operand = |operand: &Operand<'tcx>| match *operand {
+        Operand::Copy(_) | Operand::Constant(_) => true,
+        Operand::Move(_) => false,
+    };
+    match *rvalue {
+        Rvalue::Ref(..) => false,
+        Rvalue::Use(ref operand) |
+        Rvalue::Repeat(ref operand, _) |
+        Rvalue::Cast(_, ref operand, _) |
+        Rvalue::UnaryOp(_, ref operand) => is_pure_operand(operand),
+        Rvalue::BinaryOp(_, ref lhs, ref rhs) |
+        Rvalue::CheckedBinaryOp(_, ref lhs, ref rhs) => {
+            is_pure_operand(lhs) && is_pure_operand(rhs)
+        }
+        Rvalue::Aggregate(_, ref operands) => operands.iter().all(|op| is_pure_operand(op)),
+        Rvalue::Len(_) |
+        Rvalue::Discriminant(_) |
+        Rvalue::NullaryOp(..) => true,
+    }
+}
+
+/// Collects every local that is borrowed, directly or through a projection.
+pub struct BorrowedLocals(pub BitSet<Local>);
+
+impl<'tcx> Visitor<'tcx> for BorrowedLocals {
+    fn visit_rvalue(&mut self, rvalue: &Rvalue<'tcx>, location: Location) {
+        if let Rvalue::Ref(_, _, ref place) = *rvalue {
+            if let Some(local) = place.base_local() {
+                self.0.insert(local);
+            }
+        }
+        self.super_rvalue(rvalue, location)
+    }
+}
+
+/// The locals defined and used by a single statement or terminator.
+#[derive(Default)]
+struct DefsUses {
+    defs: Vec<Local>,
+    uses: Vec<Local>,
+}
+
+impl DefsUses {
+    /// Steps `live` backwards over the statement or terminator: its defs are
+    /// killed before its uses are added, so that e.g. `_1 = f(move _1)` keeps
+    /// `_1` live.
+    fn apply(self, live: &mut LiveVarSet<Local>) {
+        for local in self.defs {
+            live.remove(local);
+        }
+        for local in self.uses {
+            live.insert(local);
+        }
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for DefsUses {
+    fn visit_local(&mut self, &local: &Local, context: PlaceContext<'tcx>, _: Location) {
+        match liveness::categorize(context) {
+            Some(DefUse::Def) => self.defs.push(local),
+            Some(DefUse::Use) | Some(DefUse::Drop) => self.uses.push(local),
+            None => {}
+        }
+    }
+}
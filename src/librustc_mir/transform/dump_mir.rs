@@ -3,7 +3,7 @@
 use std::borrow::Cow;
 use std::fmt;
 use std::fs::File;
-use std::io;
+use std::io::{self, Write};
 
 use rustc::mir::Mir;
 use rustc::session::config::{OutputFilenames, OutputType};
@@ -55,6 +55,98 @@ pub fn on_mir_pass<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
     }
 }
 
+/// Dumps `mir` in place of the before and after dumps of a pass that was
+/// skipped because it is not enabled, if `-Z dump-mir-skipped` asks for it.
+pub fn on_mir_pass_skipped<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                     pass_num: &dyn fmt::Display,
+                                     pass_name: &str,
+                                     source: MirSource,
+                                     mir: &Mir<'tcx>) {
+    if tcx.sess.opts.debugging_opts.dump_mir_skipped &&
+       mir_util::dump_enabled(tcx, pass_name, source) {
+        mir_util::dump_mir(tcx,
+                           Some(pass_num),
+                           pass_name,
+                           &"skipped",
+                           source,
+                           mir,
+                           |_, _| Ok(()) );
+    }
+}
+
+/// Renders `mir` ahead of running the pass `pass_name`, if
+/// `-Z dump-mir-diff` asks for a diff of that pass; hand the result to
+/// `dump_mir_diff` once the pass has run.
+pub fn mir_diff_snapshot<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                   pass_name: &str,
+                                   source: MirSource,
+                                   mir: &Mir<'tcx>)
+                                   -> Option<String> {
+    match tcx.sess.opts.debugging_opts.dump_mir_diff {
+        Some(ref filter) if pass_name.contains(&filter[..]) => {}
+        _ => return None,
+    }
+    let mut text = vec![];
+    mir_util::write_mir_fn(tcx, source, mir, &mut |_, _| Ok(()), &mut text).ok()?;
+    String::from_utf8(text).ok()
+}
+
+/// Writes a diff between `before` (from `mir_diff_snapshot`) and `mir` into
+/// the MIR dump directory.
+pub fn dump_mir_diff<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                               pass_num: &dyn fmt::Display,
+                               pass_name: &str,
+                               source: MirSource,
+                               before: &str,
+                               mir: &Mir<'tcx>) {
+    let _: io::Result<()> = try_block! {
+        let mut after = vec![];
+        mir_util::write_mir_fn(tcx, source, mir, &mut |_, _| Ok(()), &mut after)?;
+        let after = String::from_utf8_lossy(&after);
+        let mut file = mir_util::create_dump_file(tcx, "diff", Some(pass_num), pass_name,
+                                                  &"before-after", source)?;
+        write_diff(before, &after, &mut file)?;
+    };
+}
+
+/// Writes a line diff of `before` and `after` in the unified format, with
+/// the whole text as context.
+fn write_diff(before: &str, after: &str, w: &mut dyn Write) -> io::Result<()> {
+    let before: Vec<_> = before.lines().collect();
+    let after: Vec<_> = after.lines().collect();
+
+    // `lcs[i][j]` is the length of the longest common subsequence of
+    // `before[i..]` and `after[j..]`.
+    let mut lcs = vec![vec![0usize; after.len() + 1]; before.len() + 1];
+    for i in (0..before.len()).rev() {
+        for j in (0..after.len()).rev() {
+            lcs[i][j] = if before[i] == after[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    writeln!(w, "--- before")?;
+    writeln!(w, "+++ after")?;
+    let (mut i, mut j) = (0, 0);
+    while i < before.len() || j < after.len() {
+        if i < before.len() && j < after.len() && before[i] == after[j] {
+            writeln!(w, " {}", before[i])?;
+            i += 1;
+            j += 1;
+        } else if j == after.len() || (i < before.len() && lcs[i + 1][j] >= lcs[i][j + 1]) {
+            writeln!(w, "-{}", before[i])?;
+            i += 1;
+        } else {
+            writeln!(w, "+{}", after[j])?;
+            j += 1;
+        }
+    }
+    Ok(())
+}
+
 pub fn emit_mir<'a, 'tcx>(
     tcx: TyCtxt<'a, 'tcx, 'tcx>,
     outputs: &OutputFilenames)
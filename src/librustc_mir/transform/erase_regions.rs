@@ -1,60 +1,864 @@
 //! This pass erases all early-bound regions from the types occurring in the MIR.
 //! We want to do this once just before codegen, so codegen does not have to take
 //! care erasing regions all over the place.
-//! NOTE:  We do NOT erase regions of statements that are relevant for
-//! "types-as-contracts"-validation, namely, AcquireValid, ReleaseValid
+//!
+//! MIR no longer has `EndRegion` or `Validate` statements, so there is nothing
+//! this pass has to preserve or rewrite at the statement level: every region
+//! reachable through the visitor is erased.
+//!
+//! The one deliberate exception is user type annotations (`user_ty` on local
+//! declarations, constants and ADT aggregates). They are canonicalized, so the
+//! regions in them are bound by the canonical binder rather than free, and
+//! only NLL type checking reads them, before this pass. They are left as the
+//! user wrote them: the MIR visitors never descend into them, so neither this
+//! pass nor `is_mir_region_erased` sees their regions.
 
+use rustc::hir::def_id::DefId;
 use rustc::ty::subst::Substs;
-use rustc::ty::{self, Ty, TyCtxt};
+use rustc::ty::{self, Ty, TyCtxt, TypeFlags, TypeFoldable};
+use rustc::ty::fold::{TypeFolder, TypeVisitor};
+use rustc::ty::item_path;
 use rustc::mir::*;
-use rustc::mir::visit::{MutVisitor, TyContext};
-use transform::{MirPass, MirSource};
+use rustc::mir::visit::{MutVisitor, PlaceContext, TyContext, Visitor};
+use rustc_data_structures::fx::{FxHashMap, FxHashSet};
+use transform::{MirPass, MirSource, PassResult};
+use transform::composed_visitor::ComposedVisitor;
 
-struct EraseRegionsVisitor<'a, 'tcx: 'a> {
+use std::borrow::Cow;
+use std::iter;
+use std::sync::atomic::Ordering;
+use std::thread;
+
+/// How many of the visited regions, types, substs and constants were
+/// actually changed by `EraseRegions`. Printed per function with
+/// `-Z mir-stats`.
+///
+/// Every visitor counts into its own stats, so erasing different bodies in
+/// parallel shares no mutable state; `run_pass` adds the counts to the
+/// atomic totals in the session's `PerfStats`, printed with `-Z perf-stats`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct EraseRegionsStats {
+    pub regions: usize,
+    pub tys: usize,
+    pub substs: usize,
+    pub consts: usize,
+}
+
+/// Erases the regions in types, constants and substs outside of MIR, by
+/// exactly the rules `EraseRegions` applies to the values in a MIR body.
+pub struct RegionEraser<'a, 'tcx: 'a> {
     tcx: TyCtxt<'a, 'tcx, 'tcx>,
+    /// The erased form of every type seen so far. The same types tend to
+    /// occur over and over within one function, and `EraseRegions` uses one
+    /// eraser per MIR body, so this never outlives the function it was built
+    /// for there.
+    ty_cache: FxHashMap<Ty<'tcx>, Ty<'tcx>>,
+    mode: EraseMode,
+}
+
+/// Which regions a `RegionEraser` erases.
+#[derive(Copy, Clone, PartialEq, Eq)]
+enum EraseMode {
+    /// Every free region, like `tcx.erase_regions`.
+    All,
+    /// Only late-bound regions; early-bound and other free regions are kept.
+    LateBoundOnly,
+    /// Every free region except `'static`.
+    KeepStatic,
+}
+
+impl<'a, 'tcx> RegionEraser<'a, 'tcx> {
+    pub fn new(tcx: TyCtxt<'a, 'tcx, 'tcx>) -> Self {
+        RegionEraser {
+            tcx,
+            ty_cache: FxHashMap::default(),
+            mode: EraseMode::All,
+        }
+    }
+
+    /// Creates an eraser that only erases the regions bound by a binder,
+    /// such as the `'b` in `for<'b> fn(&'b u32)`, and keeps all others.
+    pub fn late_bound_only(tcx: TyCtxt<'a, 'tcx, 'tcx>) -> Self {
+        RegionEraser {
+            mode: EraseMode::LateBoundOnly,
+            ..RegionEraser::new(tcx)
+        }
+    }
+
+    /// Creates an eraser that erases everything `new` would, except for
+    /// `'static`, so that `'static` borrows can still be told apart.
+    pub fn keep_static(tcx: TyCtxt<'a, 'tcx, 'tcx>) -> Self {
+        RegionEraser {
+            mode: EraseMode::KeepStatic,
+            ..RegionEraser::new(tcx)
+        }
+    }
+
+    pub fn erase_ty(&mut self, ty: Ty<'tcx>) -> Ty<'tcx> {
+        if !self.needs_erasure(&ty) {
+            return ty;
+        }
+        if let Some(&erased) = self.ty_cache.get(&ty) {
+            return erased;
+        }
+        let erased = self.fold(&ty);
+        if self.mode == EraseMode::All && self.tcx.sess.opts.debugging_opts.cross_check_erase {
+            if erased.has_erasable_regions() {
+                bug!("`erase_regions` erased `{:?}` to `{:?}`, which still has regions",
+                     ty, erased);
+            }
+            let again = self.tcx.erase_regions(&erased);
+            if again != erased {
+                bug!("`erase_regions` erased `{:?}` to `{:?}`, but erasing that gives `{:?}`",
+                     ty, erased, again);
+            }
+        }
+        self.ty_cache.insert(ty, erased);
+        erased
+    }
+
+    /// Erases every type in `tys` in place. This shares the cache of erased
+    /// types across the whole slice, which pays off when the types have much
+    /// in common, like those collected from a single function.
+    pub fn erase_slice(&mut self, tys: &mut [Ty<'tcx>]) {
+        for ty in tys {
+            *ty = self.erase_ty(*ty);
+        }
+    }
+
+    /// How many distinct types this eraser has erased so far. Each of them
+    /// went through the type folder exactly once.
+    pub fn cached_tys(&self) -> usize {
+        self.ty_cache.len()
+    }
+
+    pub fn erase_region(&self, region: ty::Region<'tcx>) -> ty::Region<'tcx> {
+        match (self.mode, *region) {
+            (EraseMode::LateBoundOnly, ty::ReLateBound(..)) => self.tcx.types.re_erased,
+            (EraseMode::LateBoundOnly, _) => region,
+            (EraseMode::KeepStatic, ty::ReStatic) => region,
+            _ => self.tcx.types.re_erased,
+        }
+    }
+
+    pub fn erase_const(&self, constant: &'tcx ty::Const<'tcx>) -> &'tcx ty::Const<'tcx> {
+        // This looks at both the type and the value, i.e. the substs of an
+        // unevaluated constant; evaluated values never contain regions.
+        if !self.needs_erasure(&constant) {
+            return constant;
+        }
+        let erased = self.fold(&constant);
+        // Array repeat counts are plain `u64`s in MIR, so the only way for
+        // regions to hide in a constant is through its type or an unevaluated
+        // constant's substs, both of which `erase_regions` folds.
+        debug_assert!(self.mode != EraseMode::All || !erased.has_erasable_regions(),
+                      "constant {:?} still has regions after erasure", erased);
+        erased
+    }
+
+    /// Erases anything else, such as `Substs`, `ClosureSubsts` or
+    /// `GeneratorSubsts`. For closures and generators this includes the
+    /// upvar types, so upvars captured by reference are erased as well.
+    pub fn erase<T: TypeFoldable<'tcx>>(&self, value: &T) -> T {
+        if !self.needs_erasure(value) {
+            return value.clone();
+        }
+        self.fold(value)
+    }
+
+    /// Whether erasing might change `value`. `erase_regions` also anonymizes
+    /// late-bound regions, so a value without free regions can still change,
+    /// unless its late-bound regions are anonymized already, as they are in
+    /// anything erased before. This makes running the pass a second time
+    /// cheap.
+    fn needs_erasure<T: TypeFoldable<'tcx>>(&self, value: &T) -> bool {
+        match self.mode {
+            EraseMode::LateBoundOnly => value.has_type_flags(TypeFlags::HAS_RE_LATE_BOUND),
+            EraseMode::All | EraseMode::KeepStatic => {
+                value.has_type_flags(TypeFlags::HAS_FREE_REGIONS) ||
+                    (value.has_type_flags(TypeFlags::HAS_RE_LATE_BOUND) &&
+                     value.visit_with(&mut NonAnonymizedRegions { binders: vec![] }))
+            }
+        }
+    }
+
+    fn fold<T: TypeFoldable<'tcx>>(&self, value: &T) -> T {
+        match self.mode {
+            EraseMode::All => self.tcx.erase_regions(value),
+            EraseMode::LateBoundOnly => {
+                value.fold_with(&mut LateBoundRegionEraser { tcx: self.tcx })
+            }
+            EraseMode::KeepStatic => {
+                value.fold_with(&mut StaticKeepingEraser { tcx: self.tcx })
+            }
+        }
+    }
+}
+
+/// Finds a late-bound region that `anonymize_late_bound_regions` would
+/// renumber. That numbers the distinct regions bound by each binder
+/// `BrAnon(1)`, `BrAnon(2)`, and so on, in the order they first occur in.
+/// Regions bound outside of the visited value are left alone by it.
+struct NonAnonymizedRegions {
+    /// How many distinct regions each binder around the current position has
+    /// bound so far, innermost last.
+    binders: Vec<u32>,
+}
+
+impl<'tcx> TypeVisitor<'tcx> for NonAnonymizedRegions {
+    fn visit_binder<T: TypeFoldable<'tcx>>(&mut self, t: &ty::Binder<T>) -> bool {
+        self.binders.push(0);
+        let found = t.super_visit_with(self);
+        self.binders.pop();
+        found
+    }
+
+    fn visit_region(&mut self, region: ty::Region<'tcx>) -> bool {
+        if let ty::ReLateBound(debruijn, bound_region) = *region {
+            let depth = self.binders.len();
+            if debruijn.as_usize() >= depth {
+                return false;
+            }
+            let seen = &mut self.binders[depth - 1 - debruijn.as_usize()];
+            match bound_region {
+                ty::BrAnon(index) if index >= 1 && index <= *seen => {}
+                ty::BrAnon(index) if index == *seen + 1 => *seen = index,
+                _ => return true,
+            }
+        }
+        false
+    }
+}
+
+/// Replaces every late-bound region, wherever it is bound, by `ReErased`.
+struct LateBoundRegionEraser<'a, 'tcx: 'a> {
+    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+}
+
+impl<'a, 'tcx> TypeFolder<'tcx, 'tcx> for LateBoundRegionEraser<'a, 'tcx> {
+    fn tcx<'b>(&'b self) -> TyCtxt<'b, 'tcx, 'tcx> {
+        self.tcx
+    }
+
+    fn fold_region(&mut self, region: ty::Region<'tcx>) -> ty::Region<'tcx> {
+        match *region {
+            ty::ReLateBound(..) => self.tcx.types.re_erased,
+            _ => region,
+        }
+    }
+}
+
+/// Erases free regions the way `tcx.erase_regions` does, except `'static`.
+struct StaticKeepingEraser<'a, 'tcx: 'a> {
+    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+}
+
+impl<'a, 'tcx> TypeFolder<'tcx, 'tcx> for StaticKeepingEraser<'a, 'tcx> {
+    fn tcx<'b>(&'b self) -> TyCtxt<'b, 'tcx, 'tcx> {
+        self.tcx
+    }
+
+    fn fold_binder<T>(&mut self, t: &ty::Binder<T>) -> ty::Binder<T>
+        where T: TypeFoldable<'tcx>
+    {
+        // Late-bound regions are kept, but anonymized like `erase_regions`
+        // does, so that equal erased types are still interned as one.
+        let anonymized = self.tcx.anonymize_late_bound_regions(t);
+        anonymized.super_fold_with(self)
+    }
+
+    fn fold_region(&mut self, region: ty::Region<'tcx>) -> ty::Region<'tcx> {
+        match *region {
+            ty::ReLateBound(..) | ty::ReStatic => region,
+            _ => self.tcx.types.re_erased,
+        }
+    }
+}
+
+struct EraseRegionsVisitor<'a, 'tcx: 'a> {
+    eraser: RegionEraser<'a, 'tcx>,
+    stats: EraseRegionsStats,
+    /// If set, only the declarations of these locals are erased, and
+    /// statements and terminators mentioning any other local are skipped.
+    locals: Option<FxHashSet<Local>>,
+    /// If set, the MIR is left alone, and every change that would have been
+    /// made at some location is recorded in `report` instead.
+    dry_run: bool,
+    report: Vec<(Location, String)>,
+    /// If set, every region erased by `visit_region` is recorded here, by the
+    /// location it was found at.
+    regions_by_location: Option<FxHashMap<Location, Vec<ty::Region<'tcx>>>>,
+    /// If set, every erased type is mapped here to the first type that was
+    /// erased to it.
+    shadow_types: Option<FxHashMap<Ty<'tcx>, Ty<'tcx>>>,
 }
 
 impl<'a, 'tcx> EraseRegionsVisitor<'a, 'tcx> {
     pub fn new(tcx: TyCtxt<'a, 'tcx, 'tcx>) -> Self {
+        EraseRegionsVisitor::with_eraser(RegionEraser::new(tcx))
+    }
+
+    fn with_eraser(eraser: RegionEraser<'a, 'tcx>) -> Self {
         EraseRegionsVisitor {
-            tcx,
+            eraser,
+            stats: EraseRegionsStats::default(),
+            locals: None,
+            dry_run: false,
+            report: vec![],
+            regions_by_location: None,
+            shadow_types: None,
+        }
+    }
+
+    /// Creates a visitor that only erases late-bound regions, see
+    /// `RegionEraser::late_bound_only`.
+    pub fn late_bound_only(tcx: TyCtxt<'a, 'tcx, 'tcx>) -> Self {
+        EraseRegionsVisitor::with_eraser(RegionEraser::late_bound_only(tcx))
+    }
+
+    /// Creates a visitor that keeps `'static`, see `RegionEraser::keep_static`.
+    pub fn keep_static(tcx: TyCtxt<'a, 'tcx, 'tcx>) -> Self {
+        EraseRegionsVisitor::with_eraser(RegionEraser::keep_static(tcx))
+    }
+
+    /// Creates a visitor that only reports what it would erase, see
+    /// `erase_regions_dry_run`.
+    pub fn dry_run(tcx: TyCtxt<'a, 'tcx, 'tcx>) -> Self {
+        EraseRegionsVisitor {
+            dry_run: true,
+            ..EraseRegionsVisitor::new(tcx)
+        }
+    }
+
+    /// Creates a visitor that erases as usual, but also records each region it
+    /// erases, see `erase_regions_recording`.
+    pub fn recording(tcx: TyCtxt<'a, 'tcx, 'tcx>) -> Self {
+        EraseRegionsVisitor {
+            regions_by_location: Some(FxHashMap::default()),
+            ..EraseRegionsVisitor::new(tcx)
+        }
+    }
+
+    /// Creates a visitor that erases as usual, but also remembers the types it
+    /// erases, see `erase_regions_keeping_shadow`.
+    pub fn keeping_shadow(tcx: TyCtxt<'a, 'tcx, 'tcx>) -> Self {
+        EraseRegionsVisitor {
+            shadow_types: Some(FxHashMap::default()),
+            ..EraseRegionsVisitor::new(tcx)
+        }
+    }
+
+    /// Records that `what` would change at `location`, if this is a dry
+    /// run, and logs it otherwise. Returns whether the change should actually
+    /// be made.
+    fn apply<F>(&mut self, location: Option<Location>, what: F) -> bool
+        where F: FnOnce() -> String
+    {
+        if !self.dry_run {
+            debug!("EraseRegions: {} at {:?}", what(), location);
+            return true;
+        }
+        if let Some(location) = location {
+            self.report.push((location, what()));
+        }
+        false
+    }
+
+    /// Creates a visitor that only erases the regions of `locals`: their
+    /// declared types, and the statements and terminators that mention no
+    /// other local.
+    pub fn for_locals(tcx: TyCtxt<'a, 'tcx, 'tcx>, locals: FxHashSet<Local>) -> Self {
+        EraseRegionsVisitor {
+            locals: Some(locals),
+            ..EraseRegionsVisitor::new(tcx)
+        }
+    }
+
+    /// Whether `visit` sees a local that was not selected by `for_locals`.
+    fn mentions_other_locals<F>(&self, visit: F) -> bool
+        where F: FnOnce(&mut MentionsOtherLocals)
+    {
+        match self.locals {
+            Some(ref locals) => {
+                let mut mentions = MentionsOtherLocals { locals, found: false };
+                visit(&mut mentions);
+                mentions.found
+            }
+            None => false,
+        }
+    }
+
+    /// Erases the regions in one basic block only. Visiting the whole MIR
+    /// does exactly this for every block, plus the local declarations and
+    /// the return and yield types.
+    pub fn erase_regions_in_block(&mut self,
+                                  block: BasicBlock,
+                                  data: &mut BasicBlockData<'tcx>) {
+        self.visit_basic_block_data(block, data);
+    }
+
+    /// Erases the regions in the types of the fields of a generator's layout,
+    /// which `visit_mir` does not visit.
+    fn erase_generator_layout(&mut self, layout: &mut GeneratorLayout<'tcx>) {
+        for field in &mut layout.fields {
+            let erased = self.eraser.erase_ty(field.ty);
+            if erased != field.ty {
+                self.stats.tys += 1;
+                let original = field.ty;
+                if self.apply(None, || format!("generator field `{:?}` erased", original)) {
+                    field.ty = erased;
+                }
+            }
         }
     }
 }
 
 impl<'a, 'tcx> MutVisitor<'tcx> for EraseRegionsVisitor<'a, 'tcx> {
-    fn visit_ty(&mut self, ty: &mut Ty<'tcx>, _: TyContext) {
-        *ty = self.tcx.erase_regions(ty);
+    fn visit_ty(&mut self, ty: &mut Ty<'tcx>, context: TyContext) {
+        let erased = self.eraser.erase_ty(*ty);
+        if erased == *ty {
+            return;
+        }
+        self.stats.tys += 1;
+        let location = match context {
+            TyContext::Location(location) => Some(location),
+            _ => None,
+        };
+        let original = *ty;
+        if !self.apply(location, || format!("type `{:?}` erased to `{:?}`", original, erased)) {
+            return;
+        }
+        if let Some(ref mut shadow_types) = self.shadow_types {
+            shadow_types.entry(erased).or_insert(original);
+        }
+        *ty = erased;
         self.super_ty(ty);
     }
 
-    fn visit_region(&mut self, region: &mut ty::Region<'tcx>, _: Location) {
-        *region = self.tcx.types.re_erased;
+    fn visit_region(&mut self, region: &mut ty::Region<'tcx>, location: Location) {
+        let erased = self.eraser.erase_region(*region);
+        if erased == *region {
+            return;
+        }
+        self.stats.regions += 1;
+        let original = *region;
+        if let Some(ref mut regions_by_location) = self.regions_by_location {
+            regions_by_location.entry(location).or_default().push(original);
+        }
+        if self.apply(Some(location), || format!("region `{:?}` erased", original)) {
+            *region = erased;
+        }
+    }
+
+    fn visit_const(&mut self, constant: &mut &'tcx ty::Const<'tcx>, location: Location) {
+        let erased = self.eraser.erase_const(constant);
+        if erased != *constant {
+            self.stats.consts += 1;
+            let original = *constant;
+            if !self.apply(Some(location), || format!("constant `{:?}` erased", original)) {
+                return;
+            }
+        }
+        *constant = erased;
     }
 
-    fn visit_const(&mut self, constant: &mut &'tcx ty::Const<'tcx>, _: Location) {
-        *constant = self.tcx.erase_regions(constant);
+    fn visit_substs(&mut self, substs: &mut &'tcx Substs<'tcx>, location: Location) {
+        let erased = self.eraser.erase(substs);
+        if erased != *substs {
+            self.stats.substs += 1;
+            let original = *substs;
+            if !self.apply(Some(location), || format!("substs `{:?}` erased", original)) {
+                return;
+            }
+        }
+        *substs = erased;
     }
 
-    fn visit_substs(&mut self, substs: &mut &'tcx Substs<'tcx>, _: Location) {
-        *substs = self.tcx.erase_regions(substs);
+    fn visit_closure_substs(&mut self, substs: &mut ty::ClosureSubsts<'tcx>, location: Location) {
+        let erased = self.eraser.erase(substs);
+        if erased != *substs {
+            self.stats.substs += 1;
+            let original = *substs;
+            if !self.apply(Some(location), || format!("closure substs `{:?}` erased", original)) {
+                return;
+            }
+        }
+        *substs = erased;
+    }
+
+    fn visit_generator_substs(&mut self,
+                              substs: &mut ty::GeneratorSubsts<'tcx>,
+                              location: Location) {
+        let erased = self.eraser.erase(substs);
+        if erased != *substs {
+            self.stats.substs += 1;
+            let original = *substs;
+            if !self.apply(Some(location), || format!("generator substs `{:?}` erased", original)) {
+                return;
+            }
+        }
+        *substs = erased;
+    }
+
+    fn visit_source_scope_data(&mut self, scope_data: &mut SourceScopeData) {
+        // Scopes only carry spans and their parent, so there is nothing to
+        // erase here. Should a field that can hold regions ever be added, this
+        // pattern stops compiling and the field needs to be erased above.
+        let SourceScopeData { span: _, parent_scope: _ } = *scope_data;
+        self.super_source_scope_data(scope_data);
+    }
+
+    fn visit_local_decl(&mut self, local: Local, local_decl: &mut LocalDecl<'tcx>) {
+        if let Some(ref locals) = self.locals {
+            if !locals.contains(&local) {
+                return;
+            }
+        }
+        self.super_local_decl(local, local_decl);
     }
 
     fn visit_statement(&mut self,
                        block: BasicBlock,
                        statement: &mut Statement<'tcx>,
                        location: Location) {
+        if self.mentions_other_locals(|v| v.visit_statement(block, statement, location)) {
+            return;
+        }
         self.super_statement(block, statement, location);
     }
+
+    fn visit_terminator(&mut self,
+                        block: BasicBlock,
+                        terminator: &mut Terminator<'tcx>,
+                        location: Location) {
+        if self.mentions_other_locals(|v| v.visit_terminator(block, terminator, location)) {
+            return;
+        }
+        self.super_terminator(block, terminator, location);
+    }
+}
+
+struct MentionsOtherLocals<'s> {
+    locals: &'s FxHashSet<Local>,
+    found: bool,
+}
+
+impl<'s, 'tcx> Visitor<'tcx> for MentionsOtherLocals<'s> {
+    fn visit_local(&mut self, local: &Local, _: PlaceContext<'tcx>, _: Location) {
+        if !self.locals.contains(local) {
+            self.found = true;
+        }
+    }
+}
+
+/// Whether `mir` and its promoted MIR bodies have no regions left besides
+/// `ReErased` and late-bound ones, i.e. whether `EraseRegions` would leave
+/// them as they are. User type annotations are ignored, since `EraseRegions`
+/// deliberately keeps their regions.
+pub fn is_mir_region_erased<'tcx>(mir: &Mir<'tcx>) -> bool {
+    let mut has_regions = HasRegions { found: false };
+    has_regions.visit_mir(mir);
+    for promoted in mir.promoted.iter() {
+        has_regions.visit_mir(promoted);
+    }
+    !has_regions.found
+}
+
+struct HasRegions {
+    found: bool,
+}
+
+impl HasRegions {
+    fn check<'tcx, T: TypeFoldable<'tcx>>(&mut self, value: &T) {
+        self.found |= value.has_erasable_regions();
+    }
+}
+
+impl<'tcx> Visitor<'tcx> for HasRegions {
+    fn visit_ty(&mut self, ty: &Ty<'tcx>, _: TyContext) {
+        self.check(ty);
+    }
+
+    fn visit_region(&mut self, region: &ty::Region<'tcx>, _: Location) {
+        self.check(region);
+    }
+
+    fn visit_const(&mut self, constant: &&'tcx ty::Const<'tcx>, _: Location) {
+        self.check(constant);
+    }
+
+    fn visit_substs(&mut self, substs: &&'tcx Substs<'tcx>, _: Location) {
+        self.check(substs);
+    }
+
+    fn visit_closure_substs(&mut self, substs: &ty::ClosureSubsts<'tcx>, _: Location) {
+        self.check(substs);
+    }
+
+    fn visit_generator_substs(&mut self, substs: &ty::GeneratorSubsts<'tcx>, _: Location) {
+        self.check(substs);
+    }
+}
+
+/// Returns a region-erased copy of `mir`, leaving `mir` itself untouched.
+///
+/// This is for consumers other than codegen (lints, analysis tooling) that
+/// want erased MIR without mutating the canonical one. The copy is erased by
+/// exactly the same visitor that `EraseRegions` runs in place. Unlike
+/// `visit_mir`, this also erases the promoted MIR bodies, which `run_passes`
+/// otherwise hands to the pass one by one.
+pub fn erase_regions_in_mir<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                      mir: &Mir<'tcx>)
+                                      -> Mir<'tcx> {
+    let mut mir = mir.clone();
+    EraseRegions::erase_with_stats(tcx, &mut mir);
+    for promoted in mir.promoted.iter_mut() {
+        EraseRegions::erase_with_stats(tcx, promoted);
+    }
+    debug_assert!(is_mir_region_erased(&mir), "EraseRegions left regions in {:?}", mir.span);
+    mir
+}
+
+/// Erases the regions in the single basic block `data`, for tooling that
+/// rewrote that block of already erased MIR and does not want to revisit the
+/// rest of it.
+pub fn erase_regions_in_block<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                        block: BasicBlock,
+                                        data: &mut BasicBlockData<'tcx>) {
+    EraseRegionsVisitor::new(tcx).erase_regions_in_block(block, data);
+}
+
+/// Erases the regions of the locals in `locals` only, see
+/// `EraseRegionsVisitor::for_locals`.
+pub fn erase_regions_for_locals<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                          mir: &mut Mir<'tcx>,
+                                          locals: FxHashSet<Local>) {
+    EraseRegionsVisitor::for_locals(tcx, locals).visit_mir(mir);
+}
+
+/// Erases only the late-bound regions in `mir`, keeping the early-bound ones
+/// (see `RegionEraser::late_bound_only`).
+pub fn erase_late_bound_regions_in_mir<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                                 mir: &mut Mir<'tcx>) {
+    EraseRegionsVisitor::late_bound_only(tcx).visit_mir(mir);
+}
+
+/// Erases all regions in `mir` except `'static` (see
+/// `RegionEraser::keep_static`).
+pub fn erase_regions_keeping_static<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                              mir: &mut Mir<'tcx>) {
+    EraseRegionsVisitor::keep_static(tcx).visit_mir(mir);
+}
+
+/// Erases the regions in `mir` like `EraseRegions`, and returns the regions
+/// that were erased at each location, in the order they were visited. Only
+/// the regions of borrows and the like are recorded, not those in types.
+pub fn erase_regions_recording<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                         mir: &mut Mir<'tcx>)
+                                         -> FxHashMap<Location, Vec<ty::Region<'tcx>>> {
+    let mut visitor = EraseRegionsVisitor::recording(tcx);
+    visitor.visit_mir(mir);
+    visitor.regions_by_location.unwrap_or_default()
+}
+
+/// Erases the regions in `mir` like `EraseRegions`, and returns a table
+/// mapping each erased type back to a type it was erased from, so that
+/// diagnostics about erased MIR can still name the original lifetimes, e.g.
+/// show `&'a str` rather than `&str`. This is best-effort: of the types that
+/// were erased to the same type, only the first one visited is kept.
+pub fn erase_regions_keeping_shadow<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                              mir: &mut Mir<'tcx>)
+                                              -> FxHashMap<Ty<'tcx>, Ty<'tcx>> {
+    let mut visitor = EraseRegionsVisitor::keeping_shadow(tcx);
+    visitor.visit_mir(mir);
+    visitor.shadow_types.unwrap_or_default()
+}
+
+/// Returns every change `EraseRegions` would make to the statements and
+/// terminators of `mir`, by location, without making any of them. Changes to
+/// the local declarations and the signature have no location and are not
+/// reported. `mir` is left as it is; it is only borrowed mutably because the
+/// pass visits it with a `MutVisitor`.
+pub fn erase_regions_dry_run<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                       mir: &mut Mir<'tcx>)
+                                       -> Vec<(Location, String)> {
+    let mut visitor = EraseRegionsVisitor::dry_run(tcx);
+    visitor.visit_mir(mir);
+    visitor.report
+}
+
+/// Erases the regions in `mir` and runs `visitor` over it in the same
+/// traversal, with `visitor` seeing every leaf after it has been erased. See
+/// `ComposedVisitor` for which of `visitor`'s methods are called. Returns the
+/// visitor, so that any state it collected can be read back.
+pub fn erase_regions_with<'a, 'tcx, V>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                       mir: &mut Mir<'tcx>,
+                                       visitor: V)
+                                       -> V
+    where V: MutVisitor<'tcx>
+{
+    let mut composed = ComposedVisitor::new(EraseRegionsVisitor::new(tcx), visitor);
+    composed.visit_mir(mir);
+    composed.into_inner().1
+}
+
+/// Erases the regions in `mir` directly, without going through the pass
+/// manager or needing a `MirSource`. This is meant for fuzzers and other
+/// harnesses that feed hand-built MIR to the pass.
+pub fn erase_regions_for_test<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, mir: &mut Mir<'tcx>) {
+    EraseRegions::erase_with_stats(tcx, mir);
+}
+
+/// Runs the `EraseRegions` pass on `mir`, for drivers that obtained `mir`
+/// from their own query callback and want to erase it as `run_passes` would.
+/// Unlike `erase_regions_for_test`, this goes through all of the pass: the
+/// `#[rustc_no_erase_regions]` and `-Z erase-regions-only` filters and the
+/// statistics.
+///
+/// `mir` must have been borrow-checked, since borrowck needs the regions
+/// this removes, and it must be in `MirPhase::DropsElaborated`, since
+/// `ElaborateDrops` still needs them too.
+/// Any promoted MIR in `mir.promoted` is left alone; pass those one by one.
+pub fn run_erase_regions<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                   source: MirSource,
+                                   mir: &mut Mir<'tcx>) {
+    EraseRegions.run_pass(tcx, source, mir);
+}
+
+/// Notes which function `EraseRegions` was erasing if it is dropped while
+/// the compiler panics.
+struct NoteErasingOnPanic<'a, 'tcx: 'a> {
+    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+    def_id: DefId,
+}
+
+impl<'a, 'tcx> Drop for NoteErasingOnPanic<'a, 'tcx> {
+    fn drop(&mut self) {
+        if thread::panicking() {
+            // Only the `DefId` itself: no queries while unwinding.
+            self.tcx.sess.diagnostic().note_without_error(&format!(
+                "EraseRegions was erasing the regions of {:?}", self.def_id));
+        }
+    }
+}
+
+/// Reports a bug at the span of `mir` if its signature still has regions
+/// after erasing. Codegen takes the signature from these declarations, and a
+/// region left there would otherwise only show up as a confusing failure much
+/// later. The bug is delayed, so that the other functions still get compiled.
+/// Returns whether a bug was reported.
+pub fn report_regions_left_in_signature<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                                  def_id: DefId,
+                                                  mir: &Mir<'tcx>)
+                                                  -> bool {
+    let left = iter::once(RETURN_PLACE).chain(mir.args_iter())
+        .map(|local| mir.local_decls[local].ty)
+        .find(|ty| ty.has_erasable_regions());
+    if let Some(ty) = left {
+        tcx.sess.delay_span_bug(mir.span, &format!(
+            "EraseRegions left regions in `{:?}` in the signature of {:?}", ty, def_id));
+    }
+    left.is_some()
 }
 
 pub struct EraseRegions;
 
+impl EraseRegions {
+    /// Erases the regions in `mir`, returning how much work that took.
+    pub fn erase_with_stats<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                      mir: &mut Mir<'tcx>)
+                                      -> EraseRegionsStats {
+        let mut visitor = EraseRegionsVisitor::new(tcx);
+        visitor.visit_mir(mir);
+        // The generator layout and drop shim are only built by
+        // `StateTransform`, after this pass, and from erased types. But
+        // `erase_regions_in_mir` may be given MIR from any point in the
+        // pipeline, so erase them too if they are already there.
+        if let Some(ref mut layout) = mir.generator_layout {
+            visitor.erase_generator_layout(layout);
+        }
+        if let Some(ref mut generator_drop) = mir.generator_drop {
+            visitor.visit_mir(generator_drop);
+        }
+        visitor.stats
+    }
+}
+
 impl MirPass for EraseRegions {
+    fn name<'a>(&'a self) -> Cow<'a, str> {
+        // Spelled out rather than derived from the type name, so that dump
+        // file names and tests do not depend on `type_name`'s output.
+        Cow::Borrowed("EraseRegions")
+    }
+
     fn run_pass<'a, 'tcx>(&self,
                           tcx: TyCtxt<'a, 'tcx, 'tcx>,
-                          _: MirSource,
+                          source: MirSource,
                           mir: &mut Mir<'tcx>) {
-        EraseRegionsVisitor::new(tcx).visit_mir(mir);
+        self.run_pass_with_result(tcx, source, mir);
+    }
+
+    fn run_pass_with_result<'a, 'tcx>(&self,
+                                      tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                      source: MirSource,
+                                      mir: &mut Mir<'tcx>)
+                                      -> PassResult {
+        // `#[rustc_no_erase_regions]` keeps the regions of one function, to
+        // find out whether erasing them is what breaks codegen.
+        if tcx.has_attr(source.def_id, "rustc_no_erase_regions") {
+            if source.promoted.is_none() {
+                tcx.sess.span_warn(tcx.def_span(source.def_id),
+                                   "not erasing regions because of `#[rustc_no_erase_regions]`");
+            }
+            return PassResult { changed: false };
+        }
+        // `-Z erase-regions-only` does the same for every function outside
+        // of the given path, to bisect which one breaks codegen.
+        if let Some(ref filter) = tcx.sess.opts.debugging_opts.erase_regions_only {
+            let path = item_path::with_forced_impl_filename_line(|| {
+                tcx.item_path_str(source.def_id)
+            });
+            if !path.contains(&filter[..]) {
+                return PassResult { changed: false };
+            }
+        }
+        debug_assert!(mir.phase >= MirPhase::DropsElaborated,
+                      "EraseRegions must run after ElaborateDrops, which needs regions ({:?})",
+                      source.def_id);
+        let interned_before = if tcx.sess.opts.debugging_opts.mir_mem_stats {
+            Some(tcx.interned_bytes())
+        } else {
+            None
+        };
+        let stats = {
+            // A type that cannot be erased ends up in a `bug!` deep inside the
+            // type folder, whose message does not say which function was being
+            // erased. Add that as a note while the compiler unwinds.
+            let _guard = NoteErasingOnPanic { tcx, def_id: source.def_id };
+            EraseRegions::erase_with_stats(tcx, mir)
+        };
+        report_regions_left_in_signature(tcx, source.def_id, mir);
+        if tcx.sess.opts.debugging_opts.mir_stats {
+            tcx.sess.note_without_error(&format!(
+                "EraseRegions({:?}): {} regions, {} types, {} substs, {} consts erased",
+                source.def_id, stats.regions, stats.tys, stats.substs, stats.consts));
+        }
+        let perf_stats = &tcx.sess.perf_stats;
+        if let Some(before) = interned_before {
+            let interned = tcx.interned_bytes() - before;
+            perf_stats.erase_regions_interned_bytes.fetch_add(interned, Ordering::Relaxed);
+        }
+        perf_stats.erased_regions.fetch_add(stats.regions, Ordering::Relaxed);
+        perf_stats.erased_tys.fetch_add(stats.tys, Ordering::Relaxed);
+        perf_stats.erased_substs.fetch_add(stats.substs, Ordering::Relaxed);
+        perf_stats.erased_consts.fetch_add(stats.consts, Ordering::Relaxed);
+        tcx.sess.profiler(|p| p.record_counts("erase_regions", &[
+            ("regions", stats.regions as u64),
+            ("types", stats.tys as u64),
+            ("substs", stats.substs as u64),
+            ("consts", stats.consts as u64),
+        ]));
+        // Every change is counted, so no counts means no changes.
+        PassResult { changed: stats != EraseRegionsStats::default() }
     }
 }
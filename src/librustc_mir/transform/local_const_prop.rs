@@ -0,0 +1,110 @@
+//! Propagates constants assigned to locals into later uses in the same block.
+//!
+//! This turns e.g. `_1 = const 5i32; _2 = Add(_1, _3)` into
+//! `_1 = const 5i32; _2 = Add(const 5i32, _3)`, so that `ConstFold` and
+//! `DeadStoreElimination` can clean up after it. Unlike `ConstProp`, nothing
+//! is evaluated: only locals assigned a plain `Operand::Constant` are tracked,
+//! and only until the end of their block.
+//!
+//! A tracked local is forgotten as soon as anything else is written to it,
+//! including through a projection, and at its `StorageLive` or `StorageDead`.
+//! Locals that are borrowed anywhere are never tracked, since they may be
+//! written through the reference.
+
+use rustc::mir::*;
+use rustc::mir::visit::{MutVisitor, PlaceContext, Visitor};
+use rustc::ty::TyCtxt;
+use rustc_data_structures::bit_set::BitSet;
+use rustc_data_structures::fx::FxHashMap;
+use transform::{MirPass, MirSource};
+use transform::dead_store::BorrowedLocals;
+
+pub struct LocalConstProp;
+
+impl MirPass for LocalConstProp {
+    fn min_opt_level(&self) -> usize {
+        // We only run when the MIR optimization level is > 1.
+        // At the default level 1, LLVM propagates these constants anyway.
+        2
+    }
+
+    fn run_pass<'a, 'tcx>(&self,
+                          _tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                          _source: MirSource,
+                          mir: &mut Mir<'tcx>) {
+        let borrowed = {
+            let mut borrowed = BorrowedLocals(BitSet::new_empty(mir.local_decls.len()));
+            borrowed.visit_mir(mir);
+            borrowed.0
+        };
+
+        for (block, data) in mir.basic_blocks_mut().iter_enumerated_mut() {
+            let mut propagator = Propagator {
+                borrowed: &borrowed,
+                constants: FxHashMap::default(),
+            };
+            for (statement_index, statement) in data.statements.iter_mut().enumerate() {
+                let location = Location { block, statement_index };
+                propagator.visit_statement(block, statement, location);
+                propagator.record(statement, location);
+            }
+            let location = Location { block, statement_index: data.statements.len() };
+            if let Some(ref mut terminator) = data.terminator {
+                propagator.visit_terminator(block, terminator, location);
+            }
+        }
+    }
+}
+
+struct Propagator<'b, 'tcx> {
+    borrowed: &'b BitSet<Local>,
+    /// The constant each local is known to hold at the current statement.
+    constants: FxHashMap<Local, Constant<'tcx>>,
+}
+
+impl<'b, 'tcx> Propagator<'b, 'tcx> {
+    /// Updates the known constants after `statement` has been executed.
+    fn record(&mut self, statement: &Statement<'tcx>, location: Location) {
+        let mut writes = Writes(vec![]);
+        writes.visit_statement(location.block, statement, location);
+        for local in writes.0 {
+            self.constants.remove(&local);
+        }
+
+        if let StatementKind::Assign(Place::Local(local),
+                                     box Rvalue::Use(Operand::Constant(ref constant))) =
+            statement.kind
+        {
+            if !self.borrowed.contains(local) {
+                self.constants.insert(local, (**constant).clone());
+            }
+        }
+    }
+}
+
+impl<'b, 'tcx> MutVisitor<'tcx> for Propagator<'b, 'tcx> {
+    fn visit_operand(&mut self, operand: &mut Operand<'tcx>, location: Location) {
+        let constant = match *operand {
+            Operand::Copy(Place::Local(local)) |
+            Operand::Move(Place::Local(local)) => self.constants.get(&local).cloned(),
+            _ => None,
+        };
+        if let Some(constant) = constant {
+            debug!("Propagating {:?} into {:?} at {:?}", constant, operand, location);
+            *operand = Operand::Constant(box constant);
+        }
+
+        self.super_operand(operand, location)
+    }
+}
+
+/// Collects every local whose value a statement (possibly) changes.
+struct Writes(Vec<Local>);
+
+impl<'tcx> Visitor<'tcx> for Writes {
+    fn visit_local(&mut self, &local: &Local, context: PlaceContext<'tcx>, _: Location) {
+        if context.is_mutating_use() || context.is_storage_marker() {
+            self.0.push(local);
+        }
+    }
+}
@@ -2,14 +2,18 @@ use borrow_check::nll::type_check;
 use build;
 use rustc::hir::def_id::{CrateNum, DefId, LOCAL_CRATE};
 use rustc::mir::{Mir, MirPhase, Promoted};
+use rustc::session::Session;
+use rustc::session::config::PrintRequest;
 use rustc::ty::TyCtxt;
 use rustc::ty::query::Providers;
 use rustc::ty::steal::Steal;
 use rustc::hir;
 use rustc::hir::intravisit::{self, Visitor, NestedVisitorMap};
 use rustc::util::nodemap::DefIdSet;
+use rustc_data_structures::indexed_vec::Idx;
 use rustc_data_structures::sync::Lrc;
 use std::borrow::Cow;
+use std::time::{Duration, Instant};
 use syntax::ast;
 use syntax_pos::Span;
 
@@ -20,6 +24,9 @@ pub mod check_unsafety;
 pub mod simplify_branches;
 pub mod simplify;
 pub mod erase_regions;
+pub mod check_regions_erased;
+pub mod check_nop_density;
+pub mod unerase_regions;
 pub mod no_landing_pads;
 pub mod rustc_peek;
 pub mod elaborate_drops;
@@ -28,11 +35,16 @@ pub mod promote_consts;
 pub mod qualify_consts;
 mod qualify_min_const_fn;
 pub mod remove_noop_landing_pads;
+pub mod remove_nops;
 pub mod dump_mir;
 pub mod deaggregator;
 pub mod instcombine;
+pub mod composed_visitor;
 pub mod copy_prop;
+pub mod dead_store;
 pub mod const_prop;
+pub mod const_fold;
+pub mod local_const_prop;
 pub mod generator;
 pub mod inline;
 pub mod lower_128bit;
@@ -131,6 +143,12 @@ pub fn default_name<T: ?Sized>() -> Cow<'static, str> {
     }
 }
 
+/// What running a pass did to the MIR, see `MirPass::run_pass_with_result`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PassResult {
+    pub changed: bool,
+}
+
 /// A streamlined trait that you can implement to create a pass; the
 /// pass will be named after the type, and it will consist of a main
 /// loop that goes over each available MIR and applies `run_pass`.
@@ -139,10 +157,36 @@ pub trait MirPass {
         default_name::<Self>()
     }
 
+    /// Whether the pass runs at all in this session. `run_passes` skips
+    /// disabled passes, so a pass toggled by a `-Z` flag need not check it
+    /// in `run_pass`.
+    fn is_enabled(&self, _sess: &Session) -> bool {
+        true
+    }
+
+    /// The lowest `-Z mir-opt-level` the pass runs at; `run_passes` skips it
+    /// at lower levels. Passes needed for correctness, such as
+    /// `EraseRegions`, keep the default of 0 and so always run.
+    fn min_opt_level(&self) -> usize {
+        0
+    }
+
     fn run_pass<'a, 'tcx>(&self,
                           tcx: TyCtxt<'a, 'tcx, 'tcx>,
                           source: MirSource,
                           mir: &mut Mir<'tcx>);
+
+    /// Runs the pass like `run_pass`, and also reports whether it changed the
+    /// MIR. Passes that do not keep track of that are assumed to have changed
+    /// it.
+    fn run_pass_with_result<'a, 'tcx>(&self,
+                                      tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                      source: MirSource,
+                                      mir: &mut Mir<'tcx>)
+                                      -> PassResult {
+        self.run_pass(tcx, source, mir);
+        PassResult { changed: true }
+    }
 }
 
 pub fn run_passes(
@@ -164,13 +208,54 @@ pub fn run_passes(
             promoted,
         };
         let mut index = 0;
+        // Only collected for `--print mir-pass-order`.
+        let mut pass_order = if tcx.sess.opts.prints.contains(&PrintRequest::MirPassOrder) {
+            Some(vec![])
+        } else {
+            None
+        };
         let mut run_pass = |pass: &dyn MirPass| {
+            if !pass.is_enabled(tcx.sess) ||
+               tcx.sess.opts.debugging_opts.mir_opt_level < pass.min_opt_level() {
+                dump_mir::on_mir_pass_skipped(tcx,
+                                              &format_args!("{:03}-{:03}", phase_index, index),
+                                              &pass.name(), source, mir);
+                if let Some(ref mut pass_order) = pass_order {
+                    pass_order.push(format!("{} (skipped)", pass.name()));
+                }
+                index += 1;
+                return;
+            }
+
             let run_hooks = |mir: &_, index, is_after| {
                 dump_mir::on_mir_pass(tcx, &format_args!("{:03}-{:03}", phase_index, index),
                                       &pass.name(), source, mir, is_after);
             };
             run_hooks(mir, index, false);
-            pass.run_pass(tcx, source, mir);
+            let diff_before = dump_mir::mir_diff_snapshot(tcx, &pass.name(), source, mir);
+            let start = if tcx.sess.opts.debugging_opts.time_mir_passes {
+                Some(Instant::now())
+            } else {
+                None
+            };
+            let result = pass.run_pass_with_result(tcx, source, mir);
+            if let Some(ref mut pass_order) = pass_order {
+                if result.changed {
+                    pass_order.push(pass.name().into_owned());
+                } else {
+                    pass_order.push(format!("{} (unchanged)", pass.name()));
+                }
+            }
+            if let Some(start) = start {
+                let time = start.elapsed();
+                *tcx.sess.perf_stats.mir_pass_times.lock()
+                    .entry(pass.name().into_owned())
+                    .or_insert(Duration::from_secs(0)) += time;
+            }
+            if let Some(before) = diff_before {
+                dump_mir::dump_mir_diff(tcx, &format_args!("{:03}-{:03}", phase_index, index),
+                                        &pass.name(), source, &before, mir);
+            }
             run_hooks(mir, index, true);
 
             index += 1;
@@ -180,6 +265,15 @@ pub fn run_passes(
             run_pass(*pass);
         }
 
+        if let Some(pass_order) = pass_order {
+            let promoted = match promoted {
+                Some(promoted) => format!("-promoted[{}]", promoted.index()),
+                None => String::new(),
+            };
+            println!("MIR passes of {}{} in phase {}: {}",
+                     tcx.item_path_str(def_id), promoted, phase_index, pass_order.join(", "));
+        }
+
         mir.phase = mir_phase;
     };
 
@@ -235,7 +329,7 @@ fn optimized_mir<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, def_id: DefId) -> &'tcx
     }
 
     let mut mir = tcx.mir_validated(def_id).steal();
-    run_passes(tcx, &mut mir, def_id, MirPhase::Optimized, &[
+    run_passes(tcx, &mut mir, def_id, MirPhase::DropsElaborated, &[
         // Remove all things not needed by analysis
         &no_landing_pads::NoLandingPads,
         &simplify_branches::SimplifyBranches::new("initial"),
@@ -262,11 +356,14 @@ fn optimized_mir<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, def_id: DefId) -> &'tcx
         &add_retag::AddRetag,
 
         &simplify::SimplifyCfg::new("elaborate-drops"),
+    ]);
 
-        // No lifetime analysis based on borrowing can be done from here on out.
-
+    // No lifetime analysis based on borrowing can be done from here on out.
+    run_passes(tcx, &mut mir, def_id, MirPhase::Optimized, &[
         // From here on out, regions are gone.
         &erase_regions::EraseRegions,
+        &check_regions_erased::CheckRegionsErased,
+        &unerase_regions::UneraseRegions,
 
         &lower_128bit::Lower128Bit,
 
@@ -280,11 +377,15 @@ fn optimized_mir<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>, def_id: DefId) -> &'tcx
         &generator::StateTransform,
 
         &instcombine::InstCombine,
+        &local_const_prop::LocalConstProp,
+        &const_fold::ConstFold,
         &const_prop::ConstProp,
         &simplify_branches::SimplifyBranches::new("after-const-prop"),
         &deaggregator::Deaggregator,
         &copy_prop::CopyPropagation,
+        &dead_store::DeadStoreElimination,
         &remove_noop_landing_pads::RemoveNoopLandingPads,
+        &check_nop_density::CheckNopDensity,
         &simplify::SimplifyCfg::new("final"),
         &simplify::SimplifyLocals,
 
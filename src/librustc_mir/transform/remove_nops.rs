@@ -0,0 +1,40 @@
+//! This pass removes all `Nop` statements from the MIR.
+//!
+//! Several passes (e.g., `CleanAscribeUserType` and `CleanFakeReadsAndBorrows`)
+//! delete statements by turning them into `Nop`s, because that keeps the
+//! `Location`s of all other statements stable while they run. This pass sweeps
+//! the leftovers away afterwards. Terminators, and hence their source info,
+//! are left untouched.
+//!
+//! `run_passes` does not schedule this pass: every `SimplifyCfg` already
+//! removes the `Nop`s with `remove_nops` while it merges blocks, and one runs
+//! right after each of the passes above. It is only useful for custom
+//! pipelines that need the `Nop`s gone without changing the CFG.
+
+use rustc::ty::TyCtxt;
+use rustc::mir::*;
+use rustc_data_structures::indexed_vec::IndexVec;
+use transform::{MirPass, MirSource};
+
+pub struct RemoveNops;
+
+impl MirPass for RemoveNops {
+    fn run_pass<'a, 'tcx>(&self,
+                          _tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                          _source: MirSource,
+                          mir: &mut Mir<'tcx>) {
+        remove_nops(mir.basic_blocks_mut());
+    }
+}
+
+/// Removes the `Nop` statements from every block, keeping the remaining
+/// statements in their original order.
+pub fn remove_nops<'tcx>(basic_blocks: &mut IndexVec<BasicBlock, BasicBlockData<'tcx>>) {
+    for data in basic_blocks.iter_mut() {
+        data.statements.retain(|stmt| if let StatementKind::Nop = stmt.kind {
+            false
+        } else {
+            true
+        })
+    }
+}
@@ -35,6 +35,7 @@ use rustc::mir::visit::{MutVisitor, Visitor, PlaceContext};
 use rustc::session::config::DebugInfo;
 use std::borrow::Cow;
 use transform::{MirPass, MirSource};
+use transform::remove_nops::remove_nops;
 
 pub struct SimplifyCfg { label: String }
 
@@ -253,13 +254,7 @@ impl<'a, 'tcx: 'a> CfgSimplifier<'a, 'tcx> {
     }
 
     fn strip_nops(&mut self) {
-        for blk in self.basic_blocks.iter_mut() {
-            blk.statements.retain(|stmt| if let StatementKind::Nop = stmt.kind {
-                false
-            } else {
-                true
-            })
-        }
+        remove_nops(self.basic_blocks);
     }
 }
 
@@ -0,0 +1,128 @@
+//! Checks that `EraseRegions` tolerates named regions (`-Z test-unerase`).
+//!
+//! This replaces every `ReErased` in a copy of the already erased MIR by a
+//! fresh named region, the way MIR looks before erasure, and checks that no
+//! `ReErased` is left. It then erases the copy again and checks that this
+//! gives back exactly the types of the original. The MIR itself is never
+//! changed, since codegen cannot handle the named regions; only test
+//! harnesses that want to exercise this round trip should enable the pass.
+
+use rustc::hir::def_id::DefId;
+use rustc::mir::*;
+use rustc::mir::visit::{MutVisitor, TyContext, Visitor};
+use rustc::session::Session;
+use rustc::ty::subst::Substs;
+use rustc::ty::{self, Ty, TyCtxt, TypeFoldable};
+use rustc::ty::fold::TypeFolder;
+use syntax::symbol::Symbol;
+use transform::{MirPass, MirSource};
+use transform::check_regions_erased::CollectTys;
+use transform::erase_regions::erase_regions_in_mir;
+
+use std::ptr;
+
+pub struct UneraseRegions;
+
+impl MirPass for UneraseRegions {
+    fn is_enabled(&self, sess: &Session) -> bool {
+        sess.opts.debugging_opts.test_unerase
+    }
+
+    fn run_pass<'a, 'tcx>(&self,
+                          tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                          source: MirSource,
+                          mir: &mut Mir<'tcx>) {
+        let mut unerased = mir.clone();
+        unerase_regions(tcx, source.def_id, &mut unerased);
+        let re_erased = tcx.types.re_erased;
+        if tcx.any_free_region_meets(&unerased, |region| region == re_erased) {
+            bug!("UneraseRegions left `ReErased` regions in {:?}", source.def_id);
+        }
+
+        let erased = erase_regions_in_mir(tcx, &unerased);
+        let (mut before, mut after) = (CollectTys(vec![]), CollectTys(vec![]));
+        before.visit_mir(mir);
+        after.visit_mir(&erased);
+        let same = before.0.len() == after.0.len() &&
+            before.0.iter().zip(&after.0).all(|(&a, &b)| ptr::eq(a, b));
+        if !same {
+            bug!("erasing the unerased MIR of {:?} does not give back the erased MIR",
+                 source.def_id);
+        }
+    }
+}
+
+/// Replaces every `ReErased` in `mir`, including its promoted MIR, by a
+/// fresh region named `'u0`, `'u1` and so on, free in `def_id`. User type
+/// annotations are left alone, just like `EraseRegions` leaves them.
+pub fn unerase_regions<'a, 'tcx>(tcx: TyCtxt<'a, 'tcx, 'tcx>,
+                                 def_id: DefId,
+                                 mir: &mut Mir<'tcx>) {
+    let mut unerase = UneraseRegionsVisitor {
+        folder: RegionNamer { tcx, def_id, next: 0 },
+    };
+    unerase.visit_mir(mir);
+    for promoted in mir.promoted.iter_mut() {
+        unerase.visit_mir(promoted);
+    }
+}
+
+struct RegionNamer<'a, 'tcx: 'a> {
+    tcx: TyCtxt<'a, 'tcx, 'tcx>,
+    def_id: DefId,
+    next: u32,
+}
+
+impl<'a, 'tcx> TypeFolder<'tcx, 'tcx> for RegionNamer<'a, 'tcx> {
+    fn tcx<'b>(&'b self) -> TyCtxt<'b, 'tcx, 'tcx> {
+        self.tcx
+    }
+
+    fn fold_region(&mut self, region: ty::Region<'tcx>) -> ty::Region<'tcx> {
+        match *region {
+            ty::ReErased => {
+                let name = Symbol::intern(&format!("'u{}", self.next)).as_interned_str();
+                self.next += 1;
+                self.tcx.mk_region(ty::ReFree(ty::FreeRegion {
+                    scope: self.def_id,
+                    bound_region: ty::BrNamed(self.def_id, name),
+                }))
+            }
+            _ => region,
+        }
+    }
+}
+
+struct UneraseRegionsVisitor<'a, 'tcx: 'a> {
+    folder: RegionNamer<'a, 'tcx>,
+}
+
+impl<'a, 'tcx> MutVisitor<'tcx> for UneraseRegionsVisitor<'a, 'tcx> {
+    fn visit_ty(&mut self, ty: &mut Ty<'tcx>, _: TyContext) {
+        *ty = ty.fold_with(&mut self.folder);
+    }
+
+    fn visit_region(&mut self, region: &mut ty::Region<'tcx>, _: Location) {
+        *region = region.fold_with(&mut self.folder);
+    }
+
+    fn visit_const(&mut self, constant: &mut &'tcx ty::Const<'tcx>, _: Location) {
+        *constant = constant.fold_with(&mut self.folder);
+    }
+
+    fn visit_substs(&mut self, substs: &mut &'tcx Substs<'tcx>, _: Location) {
+        *substs = substs.fold_with(&mut self.folder);
+    }
+
+    fn visit_closure_substs(&mut self, substs: &mut ty::ClosureSubsts<'tcx>, _: Location) {
+        *substs = substs.fold_with(&mut self.folder);
+    }
+
+    fn visit_generator_substs(&mut self, substs: &mut ty::GeneratorSubsts<'tcx>, _: Location) {
+        *substs = substs.fold_with(&mut self.folder);
+    }
+
+    fn visit_user_type_annotation(&mut self, _: &mut UserTypeAnnotation<'tcx>) {
+        // Kept as written, like `EraseRegions` does.
+    }
+}
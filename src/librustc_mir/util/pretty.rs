@@ -110,7 +110,8 @@ pub fn dump_enabled<'a, 'gcx, 'tcx>(
     });
     filters.split('|').any(|or_filter| {
         or_filter.split('&').all(|and_filter| {
-            and_filter == "all" || pass_name.contains(and_filter) || node_path.contains(and_filter)
+            and_filter == "all" || and_filter == "*" ||
+                pass_name.contains(and_filter) || node_path.contains(and_filter)
         })
     })
 }
@@ -968,6 +968,12 @@ pub const BUILTIN_ATTRIBUTES: &[(&str, AttributeType, AttributeGate)] = &[
                                       is just used for rustc unit tests \
                                       and will never be stable",
                                      cfg_fn!(rustc_attrs))),
+    ("rustc_no_erase_regions", Whitelisted, Gated(Stability::Unstable,
+                                                 "rustc_attrs",
+                                                 "the `#[rustc_no_erase_regions]` attribute \
+                                                  is just used for debugging rustc \
+                                                  and will never be stable",
+                                                 cfg_fn!(rustc_attrs))),
     ("rustc_inherit_overflow_checks", Whitelisted, Gated(Stability::Unstable,
                                                          "rustc_attrs",
                                                          "the `#[rustc_inherit_overflow_checks]` \
@@ -0,0 +1,49 @@
+// compile-flags: -C overflow-checks=off
+
+#![allow(exceeding_bitshifts)]
+
+fn add() -> i32 {
+    2 + 3
+}
+
+fn shift_out_of_range() -> u8 {
+    // Would trap if checked, so this must not be folded.
+    1u8 << 9
+}
+
+fn mixed(x: i32) -> i32 {
+    x + 3
+}
+
+fn main() {
+    add();
+    shift_out_of_range();
+    mixed(1);
+}
+
+// END RUST SOURCE
+// START rustc.add.ConstFold.before.mir
+//     bb0: {
+//         _0 = Add(const 2i32, const 3i32);
+//         return;
+//     }
+// END rustc.add.ConstFold.before.mir
+// START rustc.add.ConstFold.after.mir
+//     bb0: {
+//         _0 = const 5i32;
+//         return;
+//     }
+// END rustc.add.ConstFold.after.mir
+// START rustc.shift_out_of_range.ConstFold.after.mir
+//     bb0: {
+//         _0 = Shl(const 1u8, const 9i32);
+//         return;
+//     }
+// END rustc.shift_out_of_range.ConstFold.after.mir
+// START rustc.mixed.ConstFold.after.mir
+//     bb0: {
+//         ...
+//         _0 = Add(move _2, const 3i32);
+//         ...
+//     }
+// END rustc.mixed.ConstFold.after.mir
@@ -3,9 +3,35 @@ fn test(x: u32) -> u32 {
     y
 }
 
+// `b` is a copy of `a`, which is not reassigned, so `a` is used in its
+// place, and neither of them is given storage markers any more.
+fn chain(x: u32) -> u32 {
+    let a = x ^ 1;
+    let b = a;
+    b
+}
+
+// `x` is reassigned after the copy into `y`, so the copy is kept.
+fn src_reassigned(mut x: u32) -> u32 {
+    let y = x;
+    x = 1;
+    y
+}
+
+// `y` is borrowed, so the copy into it is kept.
+fn dest_borrowed(x: u32) -> u32 {
+    let mut y = x;
+    let r = &mut y;
+    *r = 1;
+    y
+}
+
 fn main() {
     // Make sure the function actually gets instantiated.
     test(0);
+    chain(0);
+    src_reassigned(0);
+    dest_borrowed(0);
 }
 
 // END RUST SOURCE
@@ -27,3 +53,53 @@ fn main() {
 //      return;
 //  }
 // END rustc.test.CopyPropagation.after.mir
+// START rustc.chain.CopyPropagation.before.mir
+//  bb0: {
+//      ...
+//      _2 = BitXor(move _3, const 1u32);
+//      ...
+//      StorageLive(_4);
+//      _4 = _2;
+//      _0 = _4;
+//      StorageDead(_4);
+//      StorageDead(_2);
+//      return;
+//  }
+// END rustc.chain.CopyPropagation.before.mir
+// START rustc.chain.CopyPropagation.after.mir
+//  bb0: {
+//      ...
+//      _2 = BitXor(move _1, const 1u32);
+//      nop;
+//      nop;
+//      nop;
+//      _0 = _2;
+//      nop;
+//      nop;
+//      return;
+//  }
+// END rustc.chain.CopyPropagation.after.mir
+// START rustc.src_reassigned.CopyPropagation.after.mir
+//  bb0: {
+//      ...
+//      _2 = _1;
+//      ...
+//      _1 = const 1u32;
+//      ...
+//      _0 = _2;
+//      ...
+//      return;
+//  }
+// END rustc.src_reassigned.CopyPropagation.after.mir
+// START rustc.dest_borrowed.CopyPropagation.after.mir
+//  bb0: {
+//      ...
+//      _2 = _1;
+//      ...
+//      _3 = &mut _2;
+//      ...
+//      _0 = _2;
+//      ...
+//      return;
+//  }
+// END rustc.dest_borrowed.CopyPropagation.after.mir
@@ -0,0 +1,71 @@
+#![allow(unused_assignments, unused_variables)]
+
+#[inline(never)]
+fn opaque() {}
+
+// The call ends the block, so `LocalConstProp` leaves the read of `a` alone.
+fn overwritten(x: i32, y: i32) -> i32 {
+    let mut a = 5;
+    a = 6;
+    opaque();
+    a
+}
+
+// Liveness does not see the uses of `a` through `r`, so its stores are kept.
+fn borrowed() -> i32 {
+    let mut a = 5;
+    let r = &mut a;
+    *r = 6;
+    a
+}
+
+// `Return` reads the return place implicitly.
+fn return_place() -> i32 {
+    5
+}
+
+fn main() {
+    overwritten(1, 2);
+    borrowed();
+    return_place();
+}
+
+// END RUST SOURCE
+// START rustc.overwritten.DeadStoreElimination.before.mir
+//     bb0: {
+//         ...
+//         _3 = const 5i32;
+//         ...
+//         _3 = const 6i32;
+//         ...
+//     }
+// END rustc.overwritten.DeadStoreElimination.before.mir
+// START rustc.overwritten.DeadStoreElimination.after.mir
+//     bb0: {
+//         ...
+//         nop;
+//         ...
+//         _3 = const 6i32;
+//         ...
+//     }
+//     bb1: {
+//         ...
+//         _0 = _3;
+//         ...
+//     }
+// END rustc.overwritten.DeadStoreElimination.after.mir
+// START rustc.borrowed.DeadStoreElimination.after.mir
+//     bb0: {
+//         ...
+//         _1 = const 5i32;
+//         ...
+//         _2 = &mut _1;
+//         ...
+//     }
+// END rustc.borrowed.DeadStoreElimination.after.mir
+// START rustc.return_place.DeadStoreElimination.after.mir
+//     bb0: {
+//         _0 = const 5i32;
+//         return;
+//     }
+// END rustc.return_place.DeadStoreElimination.after.mir
@@ -0,0 +1,18 @@
+// compile-flags: -Z dump-mir-diff=EraseRegions
+
+fn foo<'a>(x: &'a u32) -> &'a u32 {
+    x
+}
+
+fn main() {
+    foo(&0);
+}
+
+// END RUST SOURCE
+// START rustc.foo.EraseRegions.before-after.diff
+// --- before
+// +++ after
+// -fn foo(_1: &'a u32) -> &'a u32 {
+// +fn foo(_1: &u32) -> &u32 {
+// ...
+// END rustc.foo.EraseRegions.before-after.diff
@@ -0,0 +1,28 @@
+// compile-flags: -Z verify-erased
+
+// The substs of the called function and the destination of a call are
+// erased; `-Z verify-erased` checks the substs, which the MIR dump does not
+// print.
+
+fn id<T>(x: T) -> T {
+    x
+}
+
+fn call<'a>(x: &'a u32) -> &'a u32 {
+    id(x)
+}
+
+fn main() {
+    call(&1);
+}
+
+// END RUST SOURCE
+// START rustc.call.EraseRegions.after.mir
+// fn call(_1: &u32) -> &u32 {
+//     let mut _0: &u32;
+// ...
+//     let mut _2: &u32;
+// ...
+//         _0 = const id(move _2) -> bb1;
+// ...
+// END rustc.call.EraseRegions.after.mir
@@ -0,0 +1,31 @@
+// compile-flags: -Z verify-erased -C overflow-checks=off
+
+// The types of a closure's upvars are part of its substs, so erasing those
+// erases the upvars captured by reference as well; `-Z verify-erased`
+// checks that none of them keeps a region. The body reads them back with
+// their types as field projections of the closure.
+
+fn sum<'a, 'b>(a: &'a i32, b: &'b i32) -> i32 {
+    let f = move || *a + *b;
+    f()
+}
+
+fn main() {
+    sum(&1, &2);
+}
+
+// END RUST SOURCE
+// START rustc.sum-{{closure}}.EraseRegions.before.mir
+// ...
+//         _2 = (*((*_1).0: &'a i32));
+// ...
+//         _3 = (*((*_1).1: &'b i32));
+// ...
+// END rustc.sum-{{closure}}.EraseRegions.before.mir
+// START rustc.sum-{{closure}}.EraseRegions.after.mir
+// ...
+//         _2 = (*((*_1).0: &i32));
+// ...
+//         _3 = (*((*_1).1: &i32));
+// ...
+// END rustc.sum-{{closure}}.EraseRegions.after.mir
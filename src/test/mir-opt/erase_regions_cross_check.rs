@@ -0,0 +1,26 @@
+// compile-flags: -Z cross-check-erase
+
+// `-Z cross-check-erase` checks that `erase_regions` leaves no regions in
+// references, tuples and higher-ranked function pointers, and that erasing
+// them again changes nothing.
+
+fn shapes<'a, 'b>(x: &'a u32, y: (&'a u32, &'b str), f: for<'c> fn(&'c u32) -> &'c u32)
+                  -> (&'a u32, &'b str) {
+    f(x);
+    y
+}
+
+fn id(x: &u32) -> &u32 {
+    x
+}
+
+fn main() {
+    shapes(&1, (&2, "3"), id);
+}
+
+// END RUST SOURCE
+// START rustc.shapes.EraseRegions.after.mir
+// ...
+//         _0 = move _2;
+// ...
+// END rustc.shapes.EraseRegions.after.mir
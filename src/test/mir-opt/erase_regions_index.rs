@@ -0,0 +1,26 @@
+// Places projected out of a reference take their type from the declaration
+// of the base local, so erasing that declaration erases the base of an index
+// projection as well. Fields projected out of the indexed element carry
+// their own type, which is erased too.
+
+fn get<'a, T: Copy>(s: &'a [(&'a T, usize)], i: usize) -> T {
+    *s[i].0
+}
+
+fn main() {
+    get(&[(&1, 2)], 0);
+}
+
+// END RUST SOURCE
+// START rustc.get.EraseRegions.before.mir
+// fn get(_1: &'a [(&'a T, usize)], _2: usize) -> T {
+// ...
+//         _0 = (*((*_1)[_3].0: &'a T));
+// ...
+// END rustc.get.EraseRegions.before.mir
+// START rustc.get.EraseRegions.after.mir
+// fn get(_1: &[(&T, usize)], _2: usize) -> T {
+// ...
+//         _0 = (*((*_1)[_3].0: &T));
+// ...
+// END rustc.get.EraseRegions.after.mir
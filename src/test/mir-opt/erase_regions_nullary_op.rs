@@ -0,0 +1,30 @@
+#![feature(box_syntax)]
+
+// The type a `NullaryOp` allocates or measures is erased too.
+
+fn boxed<'a>(x: &'a u32) -> Box<&'a u32> {
+    let b: Box<&'a u32> = box x;
+    b
+}
+
+fn main() {
+    boxed(&1);
+}
+
+// END RUST SOURCE
+// START rustc.boxed.EraseRegions.before.mir
+// fn boxed(_1: &'a u32) -> std::boxed::Box<&'a u32> {
+// ...
+//         let _2: std::boxed::Box<&'a u32>;
+// ...
+//         _3 = Box(&'a u32);
+// ...
+// END rustc.boxed.EraseRegions.before.mir
+// START rustc.boxed.EraseRegions.after.mir
+// fn boxed(_1: &u32) -> std::boxed::Box<&u32> {
+// ...
+//         let _2: std::boxed::Box<&u32>;
+// ...
+//         _3 = Box(&u32);
+// ...
+// END rustc.boxed.EraseRegions.after.mir
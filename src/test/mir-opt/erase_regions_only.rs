@@ -0,0 +1,28 @@
+// compile-flags: -Z erase-regions-only=second::
+
+mod first {
+    pub fn kept<'a>(x: &'a u32) -> &'a u32 {
+        x
+    }
+}
+
+mod second {
+    pub fn erased<'a>(x: &'a u32) -> &'a u32 {
+        x
+    }
+}
+
+fn main() {
+    first::kept(&0);
+    second::erased(&0);
+}
+
+// END RUST SOURCE
+// START rustc.first-kept.EraseRegions.after.mir
+// fn first::kept(_1: &'a u32) -> &'a u32 {
+// ...
+// END rustc.first-kept.EraseRegions.after.mir
+// START rustc.second-erased.EraseRegions.after.mir
+// fn second::erased(_1: &u32) -> &u32 {
+// ...
+// END rustc.second-erased.EraseRegions.after.mir
@@ -0,0 +1,15 @@
+// Promoted MIR bodies are erased too.
+
+fn main() {
+    let _x: &'static i32 = &42;
+}
+
+// END RUST SOURCE
+// START rustc.main-promoted[0].EraseRegions.before.mir
+// promoted[0] in main: &'static i32 = {
+// ...
+// END rustc.main-promoted[0].EraseRegions.before.mir
+// START rustc.main-promoted[0].EraseRegions.after.mir
+// promoted[0] in main: &i32 = {
+// ...
+// END rustc.main-promoted[0].EraseRegions.after.mir
@@ -0,0 +1,21 @@
+// The return place and the arguments are erased along with the other locals.
+
+fn first<'a>(s: &'a str, _t: &'a str) -> &'a str {
+    s
+}
+
+fn main() {
+    first("a", "b");
+}
+
+// END RUST SOURCE
+// START rustc.first.EraseRegions.before.mir
+// fn first(_1: &'a str, _2: &'a str) -> &'a str {
+//     let mut _0: &'a str;
+// ...
+// END rustc.first.EraseRegions.before.mir
+// START rustc.first.EraseRegions.after.mir
+// fn first(_1: &str, _2: &str) -> &str {
+//     let mut _0: &str;
+// ...
+// END rustc.first.EraseRegions.after.mir
@@ -0,0 +1,22 @@
+// ignore-tidy-linelength
+// compile-flags: -Z verify-erased
+
+// Repeated references to generic types must be interned as the same type
+// when erased again from scratch, which `-Z verify-erased` checks.
+
+fn pick<'a, 'b, T>(x: &'a Vec<T>, y: &'b Vec<T>) -> (&'a Vec<T>, &'b Vec<T>) {
+    let pair = (x, y);
+    let again = (pair.0, pair.1);
+    again
+}
+
+fn main() {
+    let (a, b) = (vec![1], vec![2]);
+    pick(&a, &b);
+}
+
+// END RUST SOURCE
+// START rustc.pick.EraseRegions.after.mir
+// fn pick(_1: &std::vec::Vec<T>, _2: &std::vec::Vec<T>) -> (&std::vec::Vec<T>, &std::vec::Vec<T>) {
+// ...
+// END rustc.pick.EraseRegions.after.mir
@@ -0,0 +1,30 @@
+// Bindings in nested scopes are erased like any other local.
+
+fn nested<'a>(a: &'a i32) -> i32 {
+    let c;
+    {
+        let b: &'a i32 = &*a;
+        c = *b;
+    }
+    c
+}
+
+fn main() {
+    nested(&1);
+}
+
+// END RUST SOURCE
+// START rustc.nested.EraseRegions.before.mir
+// ...
+//         let _3: &'a i32;
+// ...
+//         _2 = (*_3);
+// ...
+// END rustc.nested.EraseRegions.before.mir
+// START rustc.nested.EraseRegions.after.mir
+// ...
+//         let _3: &i32;
+// ...
+//         _2 = (*_3);
+// ...
+// END rustc.nested.EraseRegions.after.mir
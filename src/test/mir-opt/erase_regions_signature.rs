@@ -0,0 +1,22 @@
+// ignore-tidy-linelength
+
+// Regions are erased from every part of a signature, however deeply they
+// are nested, and separately named regions all become the same erased one.
+
+fn second<'a, 'b, T>(pair: (&'a T, &'b Vec<Option<T>>)) -> &'b Vec<Option<T>> {
+    pair.1
+}
+
+fn main() {
+    second::<u8>((&1, &Vec::new()));
+}
+
+// END RUST SOURCE
+// START rustc.second.EraseRegions.before.mir
+// fn second(_1: (&'a T, &'b std::vec::Vec<std::option::Option<T>>)) -> &'b std::vec::Vec<std::option::Option<T>> {
+// ...
+// END rustc.second.EraseRegions.before.mir
+// START rustc.second.EraseRegions.after.mir
+// fn second(_1: (&T, &std::vec::Vec<std::option::Option<T>>)) -> &std::vec::Vec<std::option::Option<T>> {
+// ...
+// END rustc.second.EraseRegions.after.mir
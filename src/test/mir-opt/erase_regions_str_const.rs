@@ -0,0 +1,21 @@
+// compile-flags: -Z verify-erased
+
+// The type of a string constant is erased too, not just its value.
+
+fn foo<'a>() -> &'a str {
+    "hello"
+}
+
+fn main() {
+    foo();
+}
+
+// END RUST SOURCE
+// START rustc.foo.EraseRegions.before.mir
+// fn foo() -> &'a str {
+// ...
+// END rustc.foo.EraseRegions.before.mir
+// START rustc.foo.EraseRegions.after.mir
+// fn foo() -> &str {
+// ...
+// END rustc.foo.EraseRegions.after.mir
@@ -0,0 +1,27 @@
+#![feature(generators, generator_trait)]
+
+// The yield type of a generator is erased along with its return type.
+
+use std::ops::Generator;
+
+fn make<'a>(x: &'a u32) -> impl Generator<Yield = &'a u32, Return = ()> + 'a {
+    move || {
+        yield x;
+    }
+}
+
+fn main() {
+    make(&1);
+}
+
+// END RUST SOURCE
+// START rustc.make-{{closure}}.EraseRegions.before.mir
+// ...
+// yields &'a u32
+// ...
+// END rustc.make-{{closure}}.EraseRegions.before.mir
+// START rustc.make-{{closure}}.EraseRegions.after.mir
+// ...
+// yields &u32
+// ...
+// END rustc.make-{{closure}}.EraseRegions.after.mir
@@ -0,0 +1,45 @@
+// compile-flags: -C overflow-checks=off
+
+#![allow(unused_assignments)]
+
+fn propagated(x: i32) -> i32 {
+    let a = 5;
+    a + x
+}
+
+fn reassigned(x: i32) -> i32 {
+    let mut a = 5;
+    a = x;
+    a + 1
+}
+
+fn main() {
+    propagated(1);
+    reassigned(1);
+}
+
+// END RUST SOURCE
+// START rustc.propagated.LocalConstProp.after.mir
+//     bb0: {
+//         ...
+//         _2 = const 5i32;
+//         ...
+//         _3 = const 5i32;
+//         ...
+//         _0 = Add(const 5i32, move _4);
+//         ...
+//     }
+// END rustc.propagated.LocalConstProp.after.mir
+// START rustc.reassigned.LocalConstProp.after.mir
+//     bb0: {
+//         ...
+//         _2 = const 5i32;
+//         ...
+//         _2 = move _3;
+//         ...
+//         _4 = _2;
+//         ...
+//         _0 = Add(move _4, const 1i32);
+//         ...
+//     }
+// END rustc.reassigned.LocalConstProp.after.mir
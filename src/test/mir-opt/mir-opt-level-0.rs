@@ -0,0 +1,28 @@
+// compile-flags: -C overflow-checks=off -Z mir-opt-level=0 -Z dump-mir-skipped
+
+// At level 0 optimizations are skipped, while `EraseRegions`, which is
+// needed for correctness, still runs.
+
+fn add() -> i32 {
+    2 + 3
+}
+
+fn main() {
+    add();
+}
+
+// END RUST SOURCE
+// START rustc.add.EraseRegions.after.mir
+// fn add() -> i32 {
+// ...
+// END rustc.add.EraseRegions.after.mir
+// START rustc.add.ConstFold.skipped.mir
+// fn add() -> i32 {
+// ...
+//         _0 = Add(const 2i32, const 3i32);
+// ...
+// END rustc.add.ConstFold.skipped.mir
+// START rustc.add.DeadStoreElimination.skipped.mir
+// fn add() -> i32 {
+// ...
+// END rustc.add.DeadStoreElimination.skipped.mir
@@ -0,0 +1,23 @@
+// compile-flags: -C overflow-checks=off -Z mir-opt-level=2
+
+// At level 2 the optional passes run.
+
+fn add() -> i32 {
+    2 + 3
+}
+
+fn main() {
+    add();
+}
+
+// END RUST SOURCE
+// START rustc.add.ConstFold.after.mir
+// fn add() -> i32 {
+// ...
+//         _0 = const 5i32;
+// ...
+// END rustc.add.ConstFold.after.mir
+// START rustc.add.DeadStoreElimination.after.mir
+// fn add() -> i32 {
+// ...
+// END rustc.add.DeadStoreElimination.after.mir
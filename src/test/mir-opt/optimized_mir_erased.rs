@@ -0,0 +1,17 @@
+// The MIR that `optimized_mir` returns has been through `EraseRegions`, so
+// it is already the erased MIR, with none of the lifetimes of the source.
+
+fn first<'a, 'b>(x: &'a u32, _y: &'b u32) -> &'a u32 {
+    x
+}
+
+fn main() {
+    first(&1, &2);
+}
+
+// END RUST SOURCE
+// START rustc.first.PreCodegen.after.mir
+// fn first(_1: &u32, _2: &u32) -> &u32 {
+//     let mut _0: &u32;
+// ...
+// END rustc.first.PreCodegen.after.mir
@@ -0,0 +1,25 @@
+#![feature(rustc_attrs)]
+
+#[rustc_no_erase_regions]
+fn kept<'a>(x: &'a u32) -> &'a u32 {
+    x
+}
+
+fn erased<'a>(x: &'a u32) -> &'a u32 {
+    x
+}
+
+fn main() {
+    kept(&0);
+    erased(&0);
+}
+
+// END RUST SOURCE
+// START rustc.kept.EraseRegions.after.mir
+// fn kept(_1: &'a u32) -> &'a u32 {
+// ...
+// END rustc.kept.EraseRegions.after.mir
+// START rustc.erased.EraseRegions.after.mir
+// fn erased(_1: &u32) -> &u32 {
+// ...
+// END rustc.erased.EraseRegions.after.mir
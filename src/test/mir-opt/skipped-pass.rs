@@ -0,0 +1,14 @@
+// compile-flags: -Z dump-mir-skipped
+
+// `CheckRegionsErased` is disabled without `-Z verify-erased`, so it is
+// skipped instead of being run. Skipped passes are only dumped with
+// `-Z dump-mir-skipped`.
+
+fn main() {}
+
+// END RUST SOURCE
+// START rustc.main.CheckRegionsErased.skipped.mir
+// fn main() -> () {
+// ...
+// }
+// END rustc.main.CheckRegionsErased.skipped.mir
@@ -0,0 +1,18 @@
+// compile-flags: -Z test-unerase
+
+// Renaming the erased regions and erasing them again gives back the same
+// MIR, which `UneraseRegions` checks without changing the MIR itself.
+
+fn pair<'a, 'b>(x: &'a u32, y: &'b u32) -> (&'a u32, &'b u32) {
+    (x, y)
+}
+
+fn main() {
+    pair(&1, &2);
+}
+
+// END RUST SOURCE
+// START rustc.pair.UneraseRegions.after.mir
+// fn pair(_1: &u32, _2: &u32) -> (&u32, &u32) {
+// ...
+// END rustc.pair.UneraseRegions.after.mir
@@ -0,0 +1,6 @@
+#![crate_type = "lib"]
+
+pub fn bar<'a, 'b>(x: &'a [&'b (u8, &'b i16); 3]) -> &'b i16 {
+    let first: &'a &'b (u8, &'b i16) = &x[0];
+    first.1
+}
@@ -0,0 +1,5 @@
+#![crate_type = "lib"]
+
+pub fn bar(x: &u32) -> u32 {
+    *x
+}
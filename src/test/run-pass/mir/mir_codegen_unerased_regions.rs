@@ -0,0 +1,16 @@
+// run-pass
+// Codegen erases the regions of every type it takes from the MIR on its
+// own, and caches the result, so a function whose MIR kept its regions is
+// still compiled correctly.
+
+#![feature(rustc_attrs)]
+
+#[rustc_no_erase_regions]
+fn longest<'a>(x: &'a str, y: &'a str) -> &'a str {
+    if x.len() >= y.len() { x } else { y }
+}
+
+fn main() {
+    assert_eq!(longest("ab", "c"), "ab");
+    assert_eq!(longest("a", "bc"), "bc");
+}
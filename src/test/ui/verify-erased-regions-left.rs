@@ -0,0 +1,18 @@
+// `-Z verify-erased` reports the regions `EraseRegions` left in local
+// declarations, substs and borrows, not just in the types of statements.
+
+// compile-flags: -Z verify-erased
+// error-pattern: still has regions after EraseRegions
+// normalize-stderr-test "DefId\([^)]*\)" -> "DefId(..)"
+// normalize-stderr-test "DefIndex\([^)]*\)" -> "DefIndex(..)"
+
+#![feature(rustc_attrs)]
+
+#[rustc_no_erase_regions]
+fn kept<'a>(x: &'a u32) -> Option<&'a u32> {
+    Some(&*x)
+}
+
+fn main() {
+    kept(&0);
+}
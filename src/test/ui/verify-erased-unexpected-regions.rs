@@ -0,0 +1,19 @@
+// `-Z verify-erased` reports the kind of each region codegen cannot handle
+// at all, wherever it occurs, through the same path as the other regions
+// left behind.
+
+// compile-flags: -Z verify-erased
+// error-pattern: survived EraseRegions
+// normalize-stderr-test "DefId\([^)]*\)" -> "DefId(..)"
+// normalize-stderr-test "DefIndex\([^)]*\)" -> "DefIndex(..)"
+
+#![feature(rustc_attrs)]
+
+#[rustc_no_erase_regions]
+fn kept<'a>(x: &'a u32) -> &'a u32 {
+    x
+}
+
+fn main() {
+    kept(&0);
+}
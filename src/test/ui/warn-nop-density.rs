@@ -0,0 +1,51 @@
+// `-Z warn-nop-density` warns about a function that dead store elimination
+// turned into mostly `Nop`s, and not about one with just a few of them.
+
+// compile-pass
+// compile-flags: -Z warn-nop-density -Z mir-opt-level=3 -C overflow-checks=off
+// normalize-stderr-test "\d+ of the \d+ MIR" -> "N of the M MIR"
+
+#![crate_type = "lib"]
+#![allow(unused_assignments)]
+
+#[inline(never)]
+fn opaque() {}
+
+// The call ends the block, so that only the last store is read.
+pub fn dense() -> i32 { //~ WARN MIR statements are `Nop`s
+    let mut a = 0;
+    a = 1;
+    a = 2;
+    a = 3;
+    a = 4;
+    a = 5;
+    a = 6;
+    a = 7;
+    a = 8;
+    a = 9;
+    a = 10;
+    a = 11;
+    a = 12;
+    a = 13;
+    a = 14;
+    a = 15;
+    a = 16;
+    opaque();
+    a
+}
+
+// Only the first store to `a` is dead; the stores through `p` are kept.
+pub fn sparse(p: &mut (i32, i32, i32, i32, i32, i32, i32, i32, i32)) {
+    let mut a = 0;
+    a = 1;
+    opaque();
+    p.0 = a;
+    p.1 = 2;
+    p.2 = 3;
+    p.3 = 4;
+    p.4 = 5;
+    p.5 = 6;
+    p.6 = 7;
+    p.7 = 8;
+    p.8 = 9;
+}